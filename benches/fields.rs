@@ -0,0 +1,78 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fri::{
+    circle_domain, circle_fft, BabyBear, BabyBearExt4, CircleDomain, CircleFriProver,
+    ExtensionField, FriField, FriProver, Goldilocks, GoldilocksExt2, Mersenne31, Mersenne31Ext4,
+    Transcript, UniPoly,
+};
+use pasta_curves::Fp;
+
+// Proving with the same domain, expansion factor and number of colinearity checks
+// gives the same query soundness in every field. Challenges come from a field of
+// at least ~124 bits (BabyBear^4, Mersenne31^4, Goldilocks^2 or Pallas), so the folding error
+// is negligible in all of them and the configurations are at equal security.
+const LOG_DEGREE: u32 = 13;
+const EXPANSION_FACTOR: usize = 4;
+const NUM_COLINEARITY_CHECKS: usize = 16;
+
+fn bench_prove<F, E>(c: &mut Criterion, name: &str)
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+{
+    let poly_degree = 1usize << LOG_DEGREE;
+    let coeffs = (0..(poly_degree + 1)).map(|i| F::from(i as u64)).collect();
+    let poly = &UniPoly::new(coeffs);
+    let prover =
+        FriProver::<F, E>::with_params(poly_degree, EXPANSION_FACTOR, NUM_COLINEARITY_CHECKS);
+
+    let transcript = &mut Transcript::new(b"bench_fields");
+
+    c.bench_function(name, |b| {
+        b.iter(|| prover.prove_degree(black_box(poly), black_box(transcript)))
+    });
+}
+
+fn bench_mul<F: FriField>(c: &mut Criterion, name: &str) {
+    let values = (0..1024).map(|i| F::from(i as u64 + 1)).collect::<Vec<F>>();
+
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            values
+                .iter()
+                .fold(F::one(), |acc, val| acc * black_box(val))
+        })
+    });
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_prove::<Fp, Fp>(c, "prove_pallas");
+    bench_prove::<Goldilocks, GoldilocksExt2>(c, "prove_goldilocks_ext2");
+    bench_prove::<BabyBear, BabyBearExt4>(c, "prove_babybear_ext4");
+
+    bench_mul::<Fp>(c, "mul_pallas");
+    bench_mul::<Goldilocks>(c, "mul_goldilocks");
+    bench_mul::<BabyBear>(c, "mul_babybear");
+    bench_mul::<Mersenne31>(c, "mul_mersenne31");
+
+    // Mersenne31 has no large two-adic subgroup; its FFT and FRI run over the circle group.
+    let coeffs = &(0..(1u64 << LOG_DEGREE))
+        .map(Mersenne31::from)
+        .collect::<Vec<_>>();
+    let domain = CircleDomain::new(1 << LOG_DEGREE, EXPANSION_FACTOR);
+    let prover = CircleFriProver::<Mersenne31Ext4>::with_domain(domain, NUM_COLINEARITY_CHECKS);
+    let transcript = &mut Transcript::new(b"bench_fields");
+    c.bench_function("prove_mersenne31_ext4", |b| {
+        b.iter(|| prover.prove_coeffs(black_box(coeffs), black_box(transcript)))
+    });
+
+    let domain = circle_domain(LOG_DEGREE + 2);
+    let coeffs = (0..domain.len())
+        .map(|i| Mersenne31::from(i as u64))
+        .collect::<Vec<_>>();
+    c.bench_function("circle_fft_mersenne31", |b| {
+        b.iter(|| circle_fft(black_box(&coeffs), black_box(&domain)))
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
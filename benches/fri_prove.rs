@@ -11,7 +11,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     }
 
     let poly = &UniPoly::new(coeffs);
-    let prover = FriProver::<Fp>::new(poly.degree());
+    let prover = FriProver::<Fp>::new(poly.degree().unwrap());
 
     let transcript = &mut Transcript::new(b"bench_fri");
 
@@ -0,0 +1,37 @@
+use fri::{FriProver, Transcript, UniPoly};
+use pasta_curves::Fp;
+
+// Prints the proof size of FriProver for a range of degrees, expansion factors
+// and numbers of colinearity checks.
+fn main() {
+    println!(
+        "{:>8} {:>8} {:>8} {:>10} {:>10} {:>10} {:>10}",
+        "degree", "blowup", "queries", "total", "final", "paths", "leaves"
+    );
+
+    for log_degree in [4, 6, 8, 10] {
+        let degree = 1usize << log_degree;
+        let coeffs = (0..(degree + 1)).map(|i| Fp::from(i as u64)).collect();
+        let poly = UniPoly::new(coeffs);
+
+        for expansion_factor in [2, 4, 8] {
+            for num_colinearity_checks in [2, 8, 16] {
+                let prover =
+                    FriProver::<Fp>::with_params(degree, expansion_factor, num_colinearity_checks);
+                let proof = prover.prove_degree(&poly, &mut Transcript::new(b"proof_size"));
+                let size = proof.size_breakdown();
+
+                println!(
+                    "{:>8} {:>8} {:>8} {:>10} {:>10} {:>10} {:>10}",
+                    degree,
+                    expansion_factor,
+                    num_colinearity_checks,
+                    size.total(),
+                    size.final_poly,
+                    size.merkle_paths(),
+                    size.leaf_values()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::extension::{Extendable, Extension};
+use crate::field::{impl_field_ops, sqrt_tonelli_shanks, FriField};
+use ff::{Field, PrimeField};
+use rand_core::RngCore;
+use subtle::{Choice, CtOption};
+
+// The BabyBear field, p = 2^31 - 2^27 + 1 = 15 * 2^27 + 1.
+// Elements are kept in Montgomery form (x * 2^32 mod p) in a u32, so that
+// multiplication needs no division and only 32-bit lanes, which vectorizes well.
+
+const MODULUS: u32 = 0x7800_0001;
+// p^-1 mod 2^32
+const MONTY_MU: u32 = monty_mu();
+// 2^64 mod p, to convert into Montgomery form
+const MONTY_R2: u64 = ((1u128 << 64) % (MODULUS as u128)) as u64;
+
+const fn monty_mu() -> u32 {
+    // Newton iteration for p^-1 mod 2^32; each step doubles the correct bits.
+    let mut inv = 1u32;
+    let mut i = 0;
+    while i < 5 {
+        inv = inv.wrapping_mul(2u32.wrapping_sub(MODULUS.wrapping_mul(inv)));
+        i += 1;
+    }
+    inv
+}
+
+// x * 2^-32 mod p, for x < p * 2^32
+const fn monty_reduce(x: u64) -> u32 {
+    let t = (x as u32).wrapping_mul(MONTY_MU) as u64;
+    let u = t * (MODULUS as u64);
+    let (diff, under) = x.overflowing_sub(u);
+    // The low 32 bits of diff are zero by the choice of t.
+    let hi = (diff >> 32) as u32;
+    if under {
+        hi.wrapping_add(MODULUS)
+    } else {
+        hi
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BabyBear(u32);
+
+pub type BabyBearExt4 = Extension<BabyBear, 4>;
+
+impl BabyBear {
+    pub const fn new(value: u32) -> Self {
+        Self(monty_reduce((value % MODULUS) as u64 * MONTY_R2))
+    }
+
+    pub fn to_canonical_u32(&self) -> u32 {
+        monty_reduce(self.0 as u64)
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        // Both are below p < 2^31, so the sum doesn't overflow. If sum < p the
+        // subtraction wraps to a larger value and min picks the sum.
+        let sum = self.0 + rhs.0;
+        Self(sum.min(sum.wrapping_sub(MODULUS)))
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        let diff = self.0.wrapping_sub(rhs.0);
+        Self(diff.min(diff.wrapping_add(MODULUS)))
+    }
+
+    fn neg(&self) -> Self {
+        Self::zero().sub(self)
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        Self(monty_reduce(self.0 as u64 * rhs.0 as u64))
+    }
+}
+
+impl From<u64> for BabyBear {
+    fn from(value: u64) -> Self {
+        Self::new((value % MODULUS as u64) as u32)
+    }
+}
+
+impl_field_ops!(BabyBear);
+
+impl Field for BabyBear {
+    fn random(mut rng: impl RngCore) -> Self {
+        loop {
+            let value = rng.next_u32() >> 1;
+            if value < MODULUS {
+                return Self::new(value);
+            }
+        }
+    }
+
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    fn one() -> Self {
+        Self::new(1)
+    }
+
+    fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        CtOption::new(self.pow_vartime([(MODULUS - 2) as u64]), !self.is_zero())
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        sqrt_tonelli_shanks(
+            self,
+            &[(MODULUS - 1) as u64],
+            Self::multiplicative_generator(),
+        )
+    }
+}
+
+impl PrimeField for BabyBear {
+    type Repr = [u8; 4];
+
+    fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+        let value = u32::from_le_bytes(repr);
+        CtOption::new(Self::new(value), Choice::from((value < MODULUS) as u8))
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.to_canonical_u32().to_le_bytes()
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.to_canonical_u32() & 1) as u8)
+    }
+
+    const NUM_BITS: u32 = 31;
+    const CAPACITY: u32 = 30;
+    const S: u32 = 27;
+
+    fn multiplicative_generator() -> Self {
+        Self::new(31)
+    }
+
+    fn root_of_unity() -> Self {
+        // g^((p - 1) / 2^27)
+        Self::multiplicative_generator().pow_vartime([((MODULUS - 1) >> 27) as u64])
+    }
+}
+
+impl FriField for BabyBear {}
+
+// X^4 = 11. Since p = 1 mod 4, X^4 - 11 is irreducible as 11 is a non-square.
+impl Extendable<4> for BabyBear {
+    const CHARACTERISTIC: u64 = MODULUS as u64;
+
+    fn reduction() -> [Self; 4] {
+        [Self::new(11), Self::zero(), Self::zero(), Self::zero()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{prime_power_minus_one, ExtensionField};
+
+    fn values() -> Vec<u32> {
+        let mut values = vec![0, 1, 2, MODULUS - 1, MODULUS - 2, 1 << 30];
+        let mut x = 0x9e37_79b9u32;
+        for _ in 0..100 {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            values.push(x % MODULUS);
+        }
+        values
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let p = MODULUS as u64;
+        for a in values() {
+            for b in values() {
+                let (fa, fb) = (BabyBear::new(a), BabyBear::new(b));
+                let (a, b) = (a as u64, b as u64);
+                assert_eq!((fa + fb).to_canonical_u32() as u64, (a + b) % p);
+                assert_eq!((fa - fb).to_canonical_u32() as u64, (a + p - b) % p);
+                assert_eq!((fa * fb).to_canonical_u32() as u64, (a * b) % p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invert_and_sqrt() {
+        for a in values().into_iter().map(BabyBear::new) {
+            if a.is_zero_vartime() {
+                assert!(bool::from(a.invert().is_none()));
+            } else {
+                assert_eq!(a * a.invert().unwrap(), BabyBear::one());
+            }
+
+            let sqrt = a.square().sqrt().unwrap();
+            assert_eq!(sqrt.square(), a.square());
+        }
+    }
+
+    #[test]
+    fn test_generator_and_root_of_unity() {
+        // p - 1 = 2^27 * 3 * 5
+        let g = BabyBear::multiplicative_generator();
+        for q in [2, 3, 5] {
+            assert_ne!(g.pow_vartime([((MODULUS - 1) / q) as u64]), BabyBear::one());
+        }
+
+        let root = BabyBear::root_of_unity();
+        assert_eq!(root.pow_vartime([1u64 << 27]), BabyBear::one());
+        assert_ne!(root.pow_vartime([1u64 << 26]), BabyBear::one());
+    }
+
+    #[test]
+    fn test_extension_is_field() {
+        // X^(p^4) = X but X^(p^2) != X, so X has a minimal polynomial of degree 4.
+        let x = BabyBearExt4::from_base_slice(&[0, 1, 0, 0].map(BabyBear::new));
+        let frobenius = |k: usize| x * x.pow_vartime(prime_power_minus_one(MODULUS as u64, k));
+        assert_eq!(frobenius(4), x);
+        assert_ne!(frobenius(2), x);
+
+        let values = values();
+        for chunk in values.chunks_exact(4) {
+            let a = BabyBearExt4::from_base_slice(
+                &chunk.iter().map(|v| BabyBear::new(*v)).collect::<Vec<_>>(),
+            );
+            if a.is_zero_vartime() {
+                continue;
+            }
+            assert_eq!(a * a.invert().unwrap(), BabyBearExt4::one());
+            assert_eq!(BabyBearExt4::from_bytes(&a.to_bytes()), Some(a));
+        }
+    }
+}
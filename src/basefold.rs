@@ -0,0 +1,744 @@
+use crate::domain::{FoldingDomain, SubgroupDomain};
+use crate::field::{ExtensionField, FriField};
+use crate::fri_prover::{open_layer, reduce_indices, FriProver};
+use crate::fri_verifier::{check_folded, check_layer_shape, verify_layer, FriVerifier};
+use crate::hasher::{ByteDigest, Digest, MerkleHasher, Sha3Hasher};
+use crate::multilinear::{eq_eval, eq_evals, MultilinearPoly};
+use crate::parameters::FriParameters;
+use crate::transcript::FiatShamirTranscript;
+use crate::utils::sample_indices;
+use crate::FriProof;
+
+// BaseFold: a multilinear polynomial commitment built from FRI.
+// The coefficients of the multilinear polynomial are read as the coefficients
+// of a univariate polynomial, so folding the codeword with alpha fixes x_0 = alpha.
+// Each FRI folding round is paired with a sumcheck round for
+// sum_b f(b) * eq(point, b) = eval, using the same challenge, which makes
+// the final FRI constant equal to f(alpha_0, ..., alpha_{n-1}).
+// As in FRI, the polynomial and the committed codeword are over F, and the
+// challenges, the evaluation point and everything folded from them are in E.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseFoldProof<F, E = F, D = ByteDigest>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    D: Digest,
+{
+    // Evaluations of each round's sumcheck polynomial at 0, 1 and 2
+    pub sumcheck_evals: Vec<[E; 3]>,
+    pub fri_proof: FriProof<F, E, D>,
+}
+
+pub struct BaseFoldProver<F, E = F, H = Sha3Hasher>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    H: MerkleHasher<F>,
+{
+    fri_prover: FriProver<F, E, SubgroupDomain<F>, H>,
+    num_vars: usize,
+}
+
+impl<F, E, H> BaseFoldProver<F, E, H>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    H: MerkleHasher<F>,
+{
+    pub fn new(num_vars: usize) -> Self {
+        Self::with_params(num_vars, 2, 2)
+    }
+
+    // The codeword has expansion_factor * 2^num_vars points, and each proof makes
+    // num_colinearity_checks queries; both set the soundness of the commitment.
+    pub fn with_params(
+        num_vars: usize,
+        expansion_factor: usize,
+        num_colinearity_checks: usize,
+    ) -> Self {
+        assert!(num_vars > 0);
+
+        Self {
+            fri_prover: FriProver::with_params(
+                1 << num_vars,
+                expansion_factor,
+                num_colinearity_checks,
+            ),
+            num_vars,
+        }
+    }
+
+    // BaseFold folds down to a constant, so its smallest tree is shorter than
+    // in FRI, see check_cap_height.
+    pub fn cap_height(mut self, cap_height: usize) -> Self {
+        check_cap_height(&self.fri_prover.domain, self.num_vars, cap_height);
+        self.fri_prover.cap_height = cap_height;
+        self
+    }
+
+    pub fn arity(mut self, arity: usize) -> Self {
+        self.fri_prover = self.fri_prover.arity(arity);
+        self
+    }
+
+    pub fn parameters(&self) -> FriParameters {
+        FriParameters {
+            cap_height: self.fri_prover.cap_height,
+            arity: self.fri_prover.arity,
+            ..basefold_parameters::<F, E>(
+                &self.fri_prover.domain,
+                self.num_vars,
+                self.fri_prover.num_colinearity_checks,
+                self.fri_prover.allow_duplicate_queries,
+            )
+        }
+    }
+
+    fn encode(&self, poly: &MultilinearPoly<F>) -> Vec<F> {
+        assert!(poly.num_vars() == self.num_vars);
+
+        self.fri_prover.domain.encode(&poly.coeffs)
+    }
+
+    // The commitment is the cap of the tree of the codeword.
+    pub fn commit(&self, poly: &MultilinearPoly<F>) -> Vec<H::Digest> {
+        let mut tree = self.fri_prover.new_tree();
+        tree.commit(&self.encode(poly));
+        tree.cap().to_vec()
+    }
+
+    pub fn prove_eval(
+        &self,
+        poly: &MultilinearPoly<F>,
+        point: &[E],
+        transcript: &mut impl FiatShamirTranscript<F>,
+    ) -> BaseFoldProof<F, E, H::Digest> {
+        assert!(point.len() == self.num_vars);
+
+        let codeword = self.encode(poly);
+        let mut initial_tree = self.fri_prover.new_tree();
+        initial_tree.commit(&codeword);
+        absorb_statement(
+            transcript,
+            &self.parameters(),
+            initial_tree.cap(),
+            point,
+            &poly.eval(point),
+        );
+
+        let mut f_evals = poly.evals().into_iter().map(E::from).collect::<Vec<E>>();
+        let mut eq_evals = eq_evals(point);
+        let mut sumcheck_evals = vec![];
+        let mut codewords: Vec<Vec<E>> = vec![];
+        let mut trees = vec![];
+
+        for i in 0..self.num_vars {
+            // g(X) = sum_b f(X, b) * eq(X, b), evaluated at X = 0, 1, 2
+            let mut g = [E::zero(); 3];
+            for (f, e) in f_evals.chunks(2).zip(eq_evals.chunks(2)) {
+                g[0] += f[0] * e[0];
+                g[1] += f[1] * e[1];
+                g[2] += (f[1].double() - f[0]) * (e[1].double() - e[0]);
+            }
+
+            for eval in &g {
+                transcript.absorb_field(b"sumcheck", eval);
+            }
+            sumcheck_evals.push(g);
+
+            let alpha = transcript.squeeze_field::<E>(b"alpha");
+
+            f_evals = fold_evals(&f_evals, alpha);
+            eq_evals = fold_evals(&eq_evals, alpha);
+
+            let next_codeword = match codewords.last() {
+                Some(current_codeword) => self.fri_prover.fold::<E>(current_codeword, i, alpha),
+                None => self.fri_prover.fold::<F>(&codeword, i, alpha),
+            };
+
+            if i < self.num_vars - 1 {
+                let mut tree = self.fri_prover.new_tree();
+                tree.commit(&next_codeword);
+                transcript.absorb_cap(tree.cap());
+                trees.push(tree);
+            }
+
+            codewords.push(next_codeword);
+        }
+
+        // The last codeword is constant, and sent as the final polynomial.
+        let final_poly = vec![codewords.pop().unwrap()[0]];
+        for val in &final_poly {
+            transcript.absorb_field(b"final_poly", val);
+        }
+
+        let num_indices = self.fri_prover.num_colinearity_checks;
+        let mut indices = sample_indices(
+            num_indices,
+            codeword.len() / 2,
+            codeword.len() / 2,
+            self.fri_prover.allow_duplicate_queries,
+            transcript,
+        )
+        .unwrap_or_else(|e| panic!("Could not sample query indices: {:?}", e));
+
+        indices = reduce_indices(&indices, codeword.len() / 2);
+        let initial_layer = open_layer(&initial_tree, &indices);
+
+        let mut queries = vec![];
+        for (tree, codeword) in trees.iter().zip(&codewords) {
+            indices = reduce_indices(&indices, codeword.len() / 2);
+            queries.push(open_layer(tree, &indices));
+        }
+
+        BaseFoldProof {
+            sumcheck_evals,
+            fri_proof: FriProof {
+                final_poly,
+                initial_layer,
+                mask_layer: None,
+                queries,
+            },
+        }
+    }
+}
+
+pub struct BaseFoldVerifier<F, E = F, H = Sha3Hasher>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    H: MerkleHasher<F>,
+{
+    fri_verifier: FriVerifier<F, E, SubgroupDomain<F>, H>,
+    num_vars: usize,
+}
+
+impl<F, E, H> BaseFoldVerifier<F, E, H>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    H: MerkleHasher<F>,
+{
+    pub fn new(num_vars: usize) -> Self {
+        Self::with_params(num_vars, 2, 2)
+    }
+
+    pub fn with_params(
+        num_vars: usize,
+        expansion_factor: usize,
+        num_colinearity_checks: usize,
+    ) -> Self {
+        assert!(num_vars > 0);
+
+        Self {
+            fri_verifier: FriVerifier::with_params(
+                1 << num_vars,
+                expansion_factor,
+                num_colinearity_checks,
+            ),
+            num_vars,
+        }
+    }
+
+    pub fn cap_height(mut self, cap_height: usize) -> Self {
+        check_cap_height(&self.fri_verifier.domain, self.num_vars, cap_height);
+        self.fri_verifier.cap_height = cap_height;
+        self
+    }
+
+    pub fn arity(mut self, arity: usize) -> Self {
+        self.fri_verifier = self.fri_verifier.arity(arity);
+        self
+    }
+
+    pub fn parameters(&self) -> FriParameters {
+        FriParameters {
+            cap_height: self.fri_verifier.cap_height,
+            arity: self.fri_verifier.arity,
+            ..basefold_parameters::<F, E>(
+                &self.fri_verifier.domain,
+                self.num_vars,
+                self.fri_verifier.num_colinearity_checks,
+                self.fri_verifier.allow_duplicate_queries,
+            )
+        }
+    }
+
+    pub fn verify(
+        &self,
+        proof: &BaseFoldProof<F, E, H::Digest>,
+        com: &[H::Digest],
+        point: &[E],
+        eval: E,
+        transcript: &mut impl FiatShamirTranscript<F>,
+    ) {
+        assert!(point.len() == self.num_vars);
+
+        let domain = &self.fri_verifier.domain;
+        let num_indices = self.fri_verifier.num_colinearity_checks;
+        let queries = &proof.fri_proof.queries;
+        let final_poly = &proof.fri_proof.final_poly;
+
+        assert_eq!(
+            proof.sumcheck_evals.len(),
+            self.num_vars,
+            "Invalid number of sumcheck rounds"
+        );
+        assert_eq!(queries.len(), self.num_vars - 1, "Invalid number of layers");
+        assert_eq!(final_poly.len(), 1, "Final polynomial is not constant");
+        assert!(
+            proof.fri_proof.mask_layer.is_none(),
+            "BaseFold proofs don't open a mask layer"
+        );
+        let log_size = domain.size().trailing_zeros() as usize;
+        let (cap_height, arity) = (self.fri_verifier.cap_height, self.fri_verifier.arity);
+        check_layer_shape(
+            &proof.fri_proof.initial_layer,
+            log_size,
+            cap_height,
+            arity,
+            num_indices,
+        );
+        for (i, layer) in queries.iter().enumerate() {
+            check_layer_shape(layer, log_size - i - 1, cap_height, arity, num_indices);
+        }
+        assert_eq!(
+            proof.fri_proof.initial_layer.cap, com,
+            "Initial cap doesn't match the commitment"
+        );
+
+        absorb_statement(transcript, &self.parameters(), com, point, &eval);
+
+        let mut alphas = vec![];
+        let mut claim = eval;
+        for (i, g) in proof.sumcheck_evals.iter().enumerate() {
+            assert_eq!(g[0] + g[1], claim, "Sumcheck round {} failed", i);
+
+            for eval in g {
+                transcript.absorb_field(b"sumcheck", eval);
+            }
+
+            let alpha = transcript.squeeze_field::<E>(b"alpha");
+
+            claim = interpolate_quadratic(g, alpha);
+            alphas.push(alpha);
+
+            if i < self.num_vars - 1 {
+                transcript.absorb_cap(&queries[i].cap);
+            }
+        }
+
+        // The fully folded polynomial is the constant f(alpha_0, ..., alpha_{n-1}).
+        let constant = final_poly[0];
+        assert_eq!(
+            claim,
+            constant * eq_eval(point, &alphas),
+            "Final sumcheck claim mismatch"
+        );
+
+        for val in final_poly {
+            transcript.absorb_field(b"final_poly", val);
+        }
+
+        let mut indices = sample_indices(
+            num_indices,
+            domain.size() / 2,
+            domain.size() / 2,
+            self.fri_verifier.allow_duplicate_queries,
+            transcript,
+        )
+        .unwrap_or_else(|e| panic!("Could not sample query indices: {:?}", e));
+
+        indices = reduce_indices(&indices, domain.size() / 2);
+        let mut folded = verify_layer(
+            &proof.fri_proof.initial_layer,
+            &indices,
+            domain,
+            0,
+            &self.fri_verifier.hasher,
+            arity,
+            alphas[0],
+            num_indices,
+        );
+
+        for (i, layer) in queries.iter().enumerate() {
+            let half = domain.size() >> (i + 2);
+            let prev_indices = indices;
+            indices = reduce_indices(&prev_indices, half);
+            let next_folded = verify_layer(
+                layer,
+                &indices,
+                domain,
+                i + 1,
+                &self.fri_verifier.hasher,
+                arity,
+                alphas[i + 1],
+                num_indices,
+            );
+            check_folded(layer, &prev_indices, half, &folded);
+            folded = next_folded;
+        }
+
+        assert!(
+            folded.iter().all(|val| *val == constant),
+            "Final polynomial check failed"
+        );
+    }
+}
+
+// BaseFold folds all the way down to a constant, in num_vars rounds. The
+// domain description carries the expansion factor, and the number of queries
+// is num_colinearity_checks.
+fn basefold_parameters<F, E>(
+    domain: &SubgroupDomain<F>,
+    num_vars: usize,
+    num_colinearity_checks: usize,
+    allow_duplicate_queries: bool,
+) -> FriParameters
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+{
+    FriParameters::with_rounds::<F, E, _>(
+        domain,
+        num_vars,
+        1,
+        num_colinearity_checks,
+        allow_duplicate_queries,
+    )
+}
+
+// BaseFold commits to every layer but the constant one, the smallest of which
+// has 2^(log_size - num_vars + 1) points.
+fn check_cap_height<F>(domain: &SubgroupDomain<F>, num_vars: usize, cap_height: usize)
+where
+    F: FriField,
+{
+    let min_tree_height = (domain.size() >> (num_vars - 1)).trailing_zeros() as usize;
+    assert!(
+        cap_height <= min_tree_height,
+        "Cap height exceeds the smallest tree"
+    );
+}
+
+// The parameters, the commitment and the claim f(point) = eval, before any challenge
+fn absorb_statement<F, E, D>(
+    transcript: &mut impl FiatShamirTranscript<F>,
+    parameters: &FriParameters,
+    com: &[D],
+    point: &[E],
+    eval: &E,
+) where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    D: Digest,
+{
+    transcript.absorb_bytes(b"fri_parameters", &parameters.to_bytes());
+    transcript.absorb_cap(com);
+    for x in point {
+        transcript.absorb_field(b"point", x);
+    }
+    transcript.absorb_field(b"eval", eval);
+}
+
+fn fold_evals<E>(evals: &[E], alpha: E) -> Vec<E>
+where
+    E: ExtensionField,
+{
+    evals
+        .chunks(2)
+        .map(|pair| pair[0] + alpha * (pair[1] - pair[0]))
+        .collect()
+}
+
+// Evaluate the quadratic through (0, g[0]), (1, g[1]) and (2, g[2]) at x.
+fn interpolate_quadratic<E>(g: &[E; 3], x: E) -> E
+where
+    E: ExtensionField,
+{
+    let one = E::one();
+    let two = one.double();
+    let two_inv = two.invert().unwrap();
+
+    g[0] * (x - one) * (x - two) * two_inv - g[1] * x * (x - two) + g[2] * x * (x - one) * two_inv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Goldilocks, GoldilocksExt2};
+    use merlin::Transcript;
+    use pasta_curves::Fp;
+
+    fn setup(num_vars: usize) -> (MultilinearPoly<Fp>, Vec<Fp>) {
+        let coeffs = (0..(1 << num_vars))
+            .map(|i| Fp::from(i as u64 * 7 + 3))
+            .collect();
+        let point = (0..num_vars).map(|i| Fp::from(i as u64 + 11)).collect();
+
+        (MultilinearPoly::new(coeffs), point)
+    }
+
+    #[test]
+    fn test_basefold() {
+        for num_vars in 1..7 {
+            let (poly, point) = setup(num_vars);
+            let eval = poly.eval(&point);
+
+            let prover = BaseFoldProver::<Fp>::new(num_vars);
+            let com = prover.commit(&poly);
+            let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+
+            let verifier = BaseFoldVerifier::<Fp>::new(num_vars);
+            verifier.verify(
+                &proof,
+                &com,
+                &point,
+                eval,
+                &mut Transcript::new(b"test_basefold"),
+            );
+        }
+    }
+
+    #[test]
+    fn test_basefold_extension() {
+        // Over a 64-bit field, the point and the challenges are in the extension.
+        let coeffs = (0..32)
+            .map(|i| Goldilocks::from(i as u64 * 7 + 3))
+            .collect();
+        let poly = MultilinearPoly::new(coeffs);
+        let point = (0..5)
+            .map(|i| {
+                GoldilocksExt2::from_base_slice(&[Goldilocks::from(i + 11), Goldilocks::from(i)])
+            })
+            .collect::<Vec<_>>();
+        let eval = poly.eval(&point);
+
+        let prover = BaseFoldProver::<Goldilocks, GoldilocksExt2>::new(5);
+        let com = prover.commit(&poly);
+        let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+        assert_eq!(BaseFoldProof::from_bytes(&proof.to_bytes()).unwrap(), proof);
+
+        let verifier = BaseFoldVerifier::<Goldilocks, GoldilocksExt2>::new(5);
+        verifier.verify(
+            &proof,
+            &com,
+            &point,
+            eval,
+            &mut Transcript::new(b"test_basefold"),
+        );
+    }
+
+    #[test]
+    fn test_basefold_hashers() {
+        use crate::{KeccakHasher, PoseidonHasher, PoseidonTranscript};
+
+        let (poly, point) = setup(4);
+        let eval = poly.eval(&point);
+
+        let prover = BaseFoldProver::<Fp, Fp, KeccakHasher>::new(4);
+        let com = prover.commit(&poly);
+        let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+        BaseFoldVerifier::<Fp, Fp, KeccakHasher>::new(4).verify(
+            &proof,
+            &com,
+            &point,
+            eval,
+            &mut Transcript::new(b"test_basefold"),
+        );
+
+        // Field-element nodes, with a transcript over the same field
+        let prover = BaseFoldProver::<Fp, Fp, PoseidonHasher<Fp>>::new(4);
+        let com: Vec<Fp> = prover.commit(&poly);
+        let proof = prover.prove_eval(
+            &poly,
+            &point,
+            &mut PoseidonTranscript::new(b"test_basefold"),
+        );
+        BaseFoldVerifier::<Fp, Fp, PoseidonHasher<Fp>>::new(4).verify(
+            &proof,
+            &com,
+            &point,
+            eval,
+            &mut PoseidonTranscript::new(b"test_basefold"),
+        );
+    }
+
+    #[test]
+    fn test_basefold_params() {
+        let (poly, point) = setup(5);
+        let eval = poly.eval(&point);
+
+        let prover = BaseFoldProver::<Fp>::with_params(5, 8, 20);
+        let com = prover.commit(&poly);
+        let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+        assert_eq!(proof.fri_proof.initial_layer.openings.len(), 20);
+
+        let verifier = BaseFoldVerifier::<Fp>::with_params(5, 8, 20);
+        verifier.verify(
+            &proof,
+            &com,
+            &point,
+            eval,
+            &mut Transcript::new(b"test_basefold"),
+        );
+
+        // Both are bound into the transcript.
+        let parameters = verifier.parameters();
+        assert_eq!(parameters, prover.parameters());
+        assert_eq!(parameters.num_colinearity_checks, 20);
+        assert_eq!(parameters.domain_size, 8 << 5);
+        let other = BaseFoldVerifier::<Fp>::with_params(5, 4, 20).parameters();
+        assert_ne!(parameters.domain, other.domain);
+    }
+
+    #[test]
+    fn test_basefold_tree_params() {
+        let (poly, point) = setup(5);
+        let eval = poly.eval(&point);
+
+        for (arity, cap_height) in [(4, 0), (8, 1), (2, 3)] {
+            let prover = BaseFoldProver::<Fp>::with_params(5, 4, 8)
+                .arity(arity)
+                .cap_height(cap_height);
+            let com = prover.commit(&poly);
+            assert_eq!(com.len(), 1 << cap_height);
+            let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+
+            let verifier = BaseFoldVerifier::<Fp>::with_params(5, 4, 8)
+                .arity(arity)
+                .cap_height(cap_height);
+            verifier.verify(
+                &proof,
+                &com,
+                &point,
+                eval,
+                &mut Transcript::new(b"test_basefold"),
+            );
+
+            let parameters = verifier.parameters();
+            assert_eq!(parameters, prover.parameters());
+            assert_eq!(
+                (parameters.arity, parameters.cap_height),
+                (arity, cap_height)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid Merkle proof length")]
+    fn test_basefold_arity_mismatch() {
+        let (poly, point) = setup(4);
+        let eval = poly.eval(&point);
+
+        let prover = BaseFoldProver::<Fp>::new(4).arity(4);
+        let com = prover.commit(&poly);
+        let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+
+        let verifier = BaseFoldVerifier::<Fp>::new(4);
+        verifier.verify(
+            &proof,
+            &com,
+            &point,
+            eval,
+            &mut Transcript::new(b"test_basefold"),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cap height exceeds the smallest tree")]
+    fn test_basefold_cap_too_high() {
+        // The last committed layer has 4 points.
+        BaseFoldProver::<Fp>::new(4).cap_height(3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_basefold_params_mismatch() {
+        let (poly, point) = setup(5);
+        let eval = poly.eval(&point);
+
+        let prover = BaseFoldProver::<Fp>::with_params(5, 8, 20);
+        let com = prover.commit(&poly);
+        let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+
+        let verifier = BaseFoldVerifier::<Fp>::with_params(5, 4, 20);
+        verifier.verify(
+            &proof,
+            &com,
+            &point,
+            eval,
+            &mut Transcript::new(b"test_basefold"),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Sumcheck round 0 failed")]
+    fn test_basefold_wrong_eval() {
+        let (poly, point) = setup(4);
+        let eval = poly.eval(&point) + Fp::one();
+
+        let prover = BaseFoldProver::<Fp>::new(4);
+        let com = prover.commit(&poly);
+        let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+
+        let verifier = BaseFoldVerifier::<Fp>::new(4);
+        verifier.verify(
+            &proof,
+            &com,
+            &point,
+            eval,
+            &mut Transcript::new(b"test_basefold"),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "BaseFold proofs don't open a mask layer")]
+    fn test_basefold_mask_layer() {
+        let (poly, point) = setup(4);
+        let eval = poly.eval(&point);
+
+        let prover = BaseFoldProver::<Fp>::new(4);
+        let com = prover.commit(&poly);
+        let mut proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+        proof.fri_proof.mask_layer = Some(proof.fri_proof.initial_layer.clone());
+
+        let verifier = BaseFoldVerifier::<Fp>::new(4);
+        verifier.verify(
+            &proof,
+            &com,
+            &point,
+            eval,
+            &mut Transcript::new(b"test_basefold"),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_basefold_wrong_commitment() {
+        let (poly, point) = setup(4);
+        let eval = poly.eval(&point);
+
+        let prover = BaseFoldProver::<Fp>::new(4);
+        let (other, _) = setup(3);
+        let com = prover.commit(&MultilinearPoly::new(
+            other
+                .coeffs
+                .iter()
+                .chain(other.coeffs.iter())
+                .cloned()
+                .collect(),
+        ));
+        let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_basefold"));
+
+        let verifier = BaseFoldVerifier::<Fp>::new(4);
+        verifier.verify(
+            &proof,
+            &com,
+            &point,
+            eval,
+            &mut Transcript::new(b"test_basefold"),
+        );
+    }
+}
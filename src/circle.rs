@@ -0,0 +1,331 @@
+use crate::field::ExtensionField;
+use crate::mersenne31::Mersenne31;
+use ff::Field;
+
+// The circle group x^2 + y^2 = 1 over Mersenne31, of order p + 1 = 2^31, with
+// (x0, y0) + (x1, y1) = (x0 x1 - y0 y1, x0 y1 + y0 x1).
+//
+// Polynomials over a circle domain of size 2^k are written in the basis
+//   b_j(x, y) = y^j_0 * x^j_1 * pi(x)^j_2 * pi^2(x)^j_3 * ...
+// where j_i are the bits of j and pi(x) = 2x^2 - 1 is the x-coordinate of doubling.
+
+pub const CIRCLE_LOG_ORDER: u32 = 31;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CirclePoint {
+    pub x: Mersenne31,
+    pub y: Mersenne31,
+}
+
+impl CirclePoint {
+    pub fn zero() -> Self {
+        Self {
+            x: Mersenne31::one(),
+            y: Mersenne31::zero(),
+        }
+    }
+
+    // Generator of the whole circle group
+    pub fn generator() -> Self {
+        Self {
+            x: Mersenne31::new(2),
+            y: Mersenne31::new(1268011823),
+        }
+    }
+
+    // Generator of the subgroup of the given order
+    pub fn subgroup_generator(log_order: u32) -> Self {
+        assert!(log_order <= CIRCLE_LOG_ORDER);
+        let mut point = Self::generator();
+        for _ in log_order..CIRCLE_LOG_ORDER {
+            point = point.double();
+        }
+
+        point
+    }
+
+    pub fn add(&self, rhs: &Self) -> Self {
+        Self {
+            x: self.x * rhs.x - self.y * rhs.y,
+            y: self.x * rhs.y + self.y * rhs.x,
+        }
+    }
+
+    pub fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    // The inverse of (x, y) is its conjugate (x, -y).
+    pub fn neg(&self) -> Self {
+        Self {
+            x: self.x,
+            y: -self.y,
+        }
+    }
+
+    pub fn mul(&self, mut scalar: u64) -> Self {
+        let mut result = Self::zero();
+        let mut base = *self;
+        while scalar > 0 {
+            if scalar & 1 == 1 {
+                result = result.add(&base);
+            }
+            base = base.double();
+            scalar >>= 1;
+        }
+
+        result
+    }
+}
+
+// The canonic coset of size n = 2^log_size: the odd multiples of a point Q of order 2n.
+// The first half is (4i + 1) Q and the second half its conjugates, so that
+// - point i + n/2 is the conjugate of point i (same x, opposite y),
+// - for i < n/4, point i + n/4 is point i plus (-1, 0) (opposite x),
+// and doubling the first quarter gives the first half of the canonic coset of size n/2.
+pub fn circle_domain(log_size: u32) -> Vec<CirclePoint> {
+    assert!((1..CIRCLE_LOG_ORDER).contains(&log_size));
+    let n = 1usize << log_size;
+    let q = CirclePoint::subgroup_generator(log_size + 1);
+    let step = q.mul(4);
+
+    let mut first_half = Vec::with_capacity(n / 2);
+    let mut point = q;
+    for _ in 0..(n / 2) {
+        first_half.push(point);
+        point = point.add(&step);
+    }
+
+    let second_half = first_half.iter().map(|p| p.neg()).collect::<Vec<_>>();
+    [first_half, second_half].concat()
+}
+
+// x-coordinates of the next layer: pi(x) of the first half
+pub(crate) fn double_xs(xs: &[Mersenne31]) -> Vec<Mersenne31> {
+    xs[..(xs.len() / 2)]
+        .iter()
+        .map(|x| x.square().double() - Mersenne31::one())
+        .collect()
+}
+
+// Evaluate a polynomial, given by its coefficients in the circle basis, over a circle domain.
+pub fn circle_fft<E>(coeffs: &[E], domain: &[CirclePoint]) -> Vec<E>
+where
+    E: ExtensionField<BaseField = Mersenne31>,
+{
+    let n = domain.len();
+    assert!(coeffs.len() == n && n >= 2);
+
+    // f(x, y) = f_0(x) + y f_1(x)
+    let evens = coeffs.iter().step_by(2).cloned().collect::<Vec<E>>();
+    let odds = coeffs
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .cloned()
+        .collect::<Vec<E>>();
+
+    let xs = domain[..(n / 2)].iter().map(|p| p.x).collect::<Vec<_>>();
+    let evens_evals = fft_x(&evens, &xs);
+    let odds_evals = fft_x(&odds, &xs);
+
+    let mut evals = vec![E::zero(); n];
+    for i in 0..(n / 2) {
+        let y = domain[i].y;
+        evals[i] = evens_evals[i] + odds_evals[i] * y;
+        evals[i + n / 2] = evens_evals[i] - odds_evals[i] * y;
+    }
+
+    evals
+}
+
+// g(x) = g_0(pi(x)) + x g_1(pi(x)), over x-coordinates where xs[i + m/2] = -xs[i].
+fn fft_x<E>(coeffs: &[E], xs: &[Mersenne31]) -> Vec<E>
+where
+    E: ExtensionField<BaseField = Mersenne31>,
+{
+    let m = xs.len();
+    if m == 1 {
+        return coeffs.to_vec();
+    }
+
+    let evens = coeffs.iter().step_by(2).cloned().collect::<Vec<E>>();
+    let odds = coeffs
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .cloned()
+        .collect::<Vec<E>>();
+
+    let next_xs = double_xs(xs);
+    let evens_evals = fft_x(&evens, &next_xs);
+    let odds_evals = fft_x(&odds, &next_xs);
+
+    let mut evals = vec![E::zero(); m];
+    for i in 0..(m / 2) {
+        evals[i] = evens_evals[i] + odds_evals[i] * xs[i];
+        evals[i + m / 2] = evens_evals[i] - odds_evals[i] * xs[i];
+    }
+
+    evals
+}
+
+// Interpolate evaluations over a circle domain into coefficients in the circle basis.
+pub fn circle_ifft<E>(domain: &[CirclePoint], evals: &[E]) -> Vec<E>
+where
+    E: ExtensionField<BaseField = Mersenne31>,
+{
+    let n = domain.len();
+    assert!(evals.len() == n && n >= 2);
+    let two_inv = Mersenne31::from(2).invert().unwrap();
+
+    let mut evens_evals = vec![];
+    let mut odds_evals = vec![];
+    for i in 0..(n / 2) {
+        let y_inv = domain[i].y.invert().unwrap();
+        evens_evals.push((evals[i] + evals[i + n / 2]) * two_inv);
+        odds_evals.push((evals[i] - evals[i + n / 2]) * (two_inv * y_inv));
+    }
+
+    let xs = domain[..(n / 2)].iter().map(|p| p.x).collect::<Vec<_>>();
+    interleave(&ifft_x(&xs, &evens_evals), &ifft_x(&xs, &odds_evals))
+}
+
+pub(crate) fn ifft_x<E>(xs: &[Mersenne31], evals: &[E]) -> Vec<E>
+where
+    E: ExtensionField<BaseField = Mersenne31>,
+{
+    let m = xs.len();
+    if m == 1 {
+        return evals.to_vec();
+    }
+    let two_inv = Mersenne31::from(2).invert().unwrap();
+
+    let mut evens_evals = vec![];
+    let mut odds_evals = vec![];
+    for i in 0..(m / 2) {
+        let x_inv = xs[i].invert().unwrap();
+        evens_evals.push((evals[i] + evals[i + m / 2]) * two_inv);
+        odds_evals.push((evals[i] - evals[i + m / 2]) * (two_inv * x_inv));
+    }
+
+    let next_xs = double_xs(xs);
+    interleave(
+        &ifft_x(&next_xs, &evens_evals),
+        &ifft_x(&next_xs, &odds_evals),
+    )
+}
+
+// Evaluate coefficients in the x-part of the circle basis at a single x:
+// g(x) = g_0(pi(x)) + x g_1(pi(x)).
+pub(crate) fn eval_x<E>(coeffs: &[E], x: Mersenne31) -> E
+where
+    E: ExtensionField<BaseField = Mersenne31>,
+{
+    if coeffs.len() <= 1 {
+        return coeffs.first().copied().unwrap_or(E::zero());
+    }
+
+    let evens = coeffs.iter().step_by(2).copied().collect::<Vec<_>>();
+    let odds = coeffs
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .copied()
+        .collect::<Vec<_>>();
+    let pi_x = x.square().double() - Mersenne31::one();
+    eval_x(&evens, pi_x) + eval_x(&odds, pi_x) * x
+}
+
+fn interleave<E: Copy>(evens: &[E], odds: &[E]) -> Vec<E> {
+    evens
+        .iter()
+        .zip(odds.iter())
+        .flat_map(|(e, o)| [*e, *o])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mersenne31::Mersenne31Ext4;
+
+    // b_j(x, y) evaluated directly
+    fn basis_eval(j: usize, point: &CirclePoint) -> Mersenne31 {
+        let mut result = if j & 1 == 1 {
+            point.y
+        } else {
+            Mersenne31::one()
+        };
+        let mut x = point.x;
+        let mut j = j >> 1;
+        while j > 0 {
+            if j & 1 == 1 {
+                result *= x;
+            }
+            x = x.square().double() - Mersenne31::one();
+            j >>= 1;
+        }
+
+        result
+    }
+
+    #[test]
+    fn test_circle_group() {
+        let g = CirclePoint::generator();
+        assert_eq!(g.x.square() + g.y.square(), Mersenne31::one());
+
+        // g has order exactly 2^31
+        let half = CirclePoint::subgroup_generator(1);
+        assert_eq!(
+            half,
+            CirclePoint {
+                x: -Mersenne31::one(),
+                y: Mersenne31::zero()
+            }
+        );
+        assert_eq!(half.double(), CirclePoint::zero());
+        assert_eq!(g.mul(1 << 31), CirclePoint::zero());
+    }
+
+    #[test]
+    fn test_circle_domain() {
+        for log_size in 1..8 {
+            let domain = circle_domain(log_size);
+            let n = domain.len();
+
+            for (i, point) in domain.iter().enumerate() {
+                assert!(!domain[..i].contains(point));
+            }
+            for i in 0..(n / 2) {
+                assert_eq!(domain[i + n / 2], domain[i].neg());
+            }
+            for i in 0..(n / 4) {
+                assert_eq!(domain[i + n / 4].x, -domain[i].x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_circle_fft() {
+        for log_size in 1..8 {
+            let domain = circle_domain(log_size);
+            let coeffs = (0..domain.len())
+                .map(|i| Mersenne31Ext4::from(Mersenne31::from(i as u64 * 7 + 1)))
+                .collect::<Vec<_>>();
+
+            let evals = circle_fft(&coeffs, &domain);
+            for (point, eval) in domain.iter().zip(evals.iter()) {
+                let expected = coeffs
+                    .iter()
+                    .enumerate()
+                    .fold(Mersenne31Ext4::zero(), |acc, (j, c)| {
+                        acc + *c * basis_eval(j, point)
+                    });
+                assert_eq!(*eval, expected);
+            }
+
+            assert_eq!(circle_ifft(&domain, &evals), coeffs);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::circle::{circle_domain, circle_fft, double_xs, eval_x, ifft_x, CirclePoint};
+use crate::domain::{assert_can_fold, num_folding_rounds, truncate_poly, FoldingDomain};
+use crate::field::ExtensionField;
+use crate::fri_prover::FriProver;
+use crate::fri_verifier::FriVerifier;
+use crate::mersenne31::Mersenne31;
+use ff::Field;
+
+// Circle FRI: codewords are evaluations over a canonic circle domain, and
+// polynomials are given by their coefficients in the circle basis (see circle.rs).
+//
+// The first fold pairs each point with its conjugate and projects onto x:
+//   f(x, y) = f_0(x) + y f_1(x)  ->  f_0(x) + alpha f_1(x)
+// and every later fold pairs x with -x and maps both to pi(x) = 2x^2 - 1:
+//   g(x) = g_0(pi(x)) + x g_1(pi(x))  ->  g_0(x) + alpha g_1(x)
+// Both halve the number of circle basis coefficients, like the fold over subgroups.
+
+pub type CircleFriProver<E = Mersenne31> = FriProver<Mersenne31, E, CircleDomain>;
+pub type CircleFriVerifier<E = Mersenne31> = FriVerifier<Mersenne31, E, CircleDomain>;
+
+pub struct CircleDomain {
+    points: Vec<CirclePoint>,
+    // x-coordinates of the layers after the first fold
+    xs: Vec<Vec<Mersenne31>>,
+    // 1/y for the first layer, 1/x for the later ones
+    inverses: Vec<Vec<Mersenne31>>,
+    two_inv: Mersenne31,
+    // Polynomials must have at most max_degree circle basis coefficients
+    max_degree: usize,
+}
+
+impl CircleDomain {
+    pub fn new(max_degree: usize, expansion_factor: usize) -> Self {
+        assert!(max_degree.is_power_of_two());
+        assert!(expansion_factor.is_power_of_two() && expansion_factor > 1);
+
+        let log_size = ((max_degree * expansion_factor) as f64).log2() as u32;
+        let points = circle_domain(log_size);
+
+        // Points i and i + n/2 are conjugate, and fold into x-coordinate i.
+        let mut xs = vec![points[..(points.len() / 2)]
+            .iter()
+            .map(|p| p.x)
+            .collect::<Vec<_>>()];
+        while xs.last().unwrap().len() > 1 {
+            let next_xs = double_xs(xs.last().unwrap());
+            xs.push(next_xs);
+        }
+
+        let mut inverses = vec![points.iter().map(|p| p.y.invert().unwrap()).collect()];
+        // x is only zero at points of order 4, in the last layer, which is never folded.
+        for layer in &xs[..(xs.len() - 1)] {
+            inverses.push(layer.iter().map(|x| x.invert().unwrap()).collect());
+        }
+
+        let domain = Self {
+            points,
+            xs,
+            inverses,
+            two_inv: Mersenne31::from(2).invert().unwrap(),
+            max_degree,
+        };
+        assert_can_fold(&domain);
+        domain
+    }
+}
+
+impl FoldingDomain<Mersenne31> for CircleDomain {
+    fn size(&self) -> usize {
+        self.points.len()
+    }
+
+    fn num_rounds(&self) -> usize {
+        num_folding_rounds(self.size(), self.max_degree)
+    }
+
+    fn encode(&self, coeffs: &[Mersenne31]) -> Vec<Mersenne31> {
+        assert!(coeffs.len() <= self.max_degree);
+        let mut coeffs = coeffs.to_vec();
+        coeffs.resize(self.size(), Mersenne31::zero());
+
+        circle_fft(&coeffs, &self.points)
+    }
+
+    fn fold<E>(&self, round: usize, index: usize, a: E, b: E, alpha: E) -> E
+    where
+        E: ExtensionField<BaseField = Mersenne31>,
+    {
+        // f_0 = (a + b) / 2 and f_1 = (a - b) / 2y in the first round, with x in
+        // place of y afterwards.
+        let inv = self.inverses[round][index];
+        (a + b + alpha * (a - b) * inv) * self.two_inv
+    }
+
+    fn final_poly_len(&self, round: usize) -> usize {
+        self.max_degree >> round
+    }
+
+    fn final_poly<E>(&self, codeword: &[E], round: usize) -> Vec<E>
+    where
+        E: ExtensionField<BaseField = Mersenne31>,
+    {
+        // After the first fold the codewords only depend on x.
+        assert!(round > 0);
+        let coeffs = ifft_x(&self.xs[round - 1], codeword);
+        truncate_poly(coeffs, self.final_poly_len(round))
+    }
+
+    fn eval_final_poly<E>(&self, coeffs: &[E], round: usize, index: usize) -> E
+    where
+        E: ExtensionField<BaseField = Mersenne31>,
+    {
+        eval_x(coeffs, self.xs[round - 1][index])
+    }
+
+    fn description(&self) -> Vec<u8> {
+        // The canonic coset is fixed by its size.
+        let mut bytes = b"circle".to_vec();
+        bytes.extend_from_slice(&(self.size() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.max_degree as u64).to_le_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mersenne31::Mersenne31Ext4;
+    use crate::FriProof;
+    use merlin::Transcript;
+
+    fn prove<E>(max_degree: usize, num_coeffs: usize) -> FriProof<Mersenne31, E>
+    where
+        E: ExtensionField<BaseField = Mersenne31>,
+    {
+        let coeffs = (0..num_coeffs)
+            .map(|i| Mersenne31::from(i as u64 * 3 + 1))
+            .collect::<Vec<_>>();
+
+        let prover = CircleFriProver::<E>::with_domain(CircleDomain::new(max_degree, 4), 4);
+        prover.prove_coeffs(&coeffs, &mut Transcript::new(b"test_circle_fri"))
+    }
+
+    #[test]
+    fn test_prove() {
+        for max_degree in [16, 64] {
+            let proof = prove::<Mersenne31>(max_degree, max_degree);
+            let verifier =
+                CircleFriVerifier::<Mersenne31>::with_domain(CircleDomain::new(max_degree, 4), 4);
+            let com = &proof.initial_layer.cap.clone();
+            verifier.verify(&proof, com, &mut Transcript::new(b"test_circle_fri"));
+
+            let proof = prove::<Mersenne31Ext4>(max_degree, max_degree);
+            let verifier = CircleFriVerifier::<Mersenne31Ext4>::with_domain(
+                CircleDomain::new(max_degree, 4),
+                4,
+            );
+            let com = &proof.initial_layer.cap.clone();
+            verifier.verify(&proof, com, &mut Transcript::new(b"test_circle_fri"));
+        }
+    }
+
+    #[test]
+    fn test_prove_small_domains() {
+        for max_degree in [2, 4] {
+            let proof = prove::<Mersenne31>(max_degree, max_degree);
+            let verifier =
+                CircleFriVerifier::<Mersenne31>::with_domain(CircleDomain::new(max_degree, 4), 4);
+            let com = &proof.initial_layer.cap.clone();
+            verifier.verify(&proof, com, &mut Transcript::new(b"test_circle_fri"));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Final polynomial has too high degree")]
+    fn test_too_high_degree() {
+        // 32 coefficients on the domain of size 64, checked against 16
+        let coeffs = (0..32)
+            .map(|i| Mersenne31::from(i as u64 + 1))
+            .collect::<Vec<_>>();
+        let prover = CircleFriProver::<Mersenne31Ext4>::with_domain(CircleDomain::new(32, 2), 4);
+        let proof = prover.prove_coeffs(&coeffs, &mut Transcript::new(b"test_circle_fri"));
+
+        let verifier =
+            CircleFriVerifier::<Mersenne31Ext4>::with_domain(CircleDomain::new(16, 4), 4);
+        let com = &proof.initial_layer.cap.clone();
+        verifier.verify(&proof, com, &mut Transcript::new(b"test_circle_fri"));
+    }
+}
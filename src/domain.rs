@@ -0,0 +1,268 @@
+use crate::evaluations::Evaluations;
+use crate::field::{subgroup, ExtensionField, FriField};
+use crate::unipoly::UniPoly;
+
+// The evaluation domains of the FRI layers. Layer i has size() >> i points, and
+// points j and j + n/2 of a layer of size n fold into point j of the next layer.
+pub trait FoldingDomain<F: FriField> {
+    // Size of the initial domain
+    fn size(&self) -> usize;
+
+    // Number of folding rounds before the codeword is sent in the clear
+    fn num_rounds(&self) -> usize;
+
+    // The codeword of the polynomial with the given coefficients
+    fn encode(&self, coeffs: &[F]) -> Vec<F>;
+
+    // The value at point `index` of layer `round + 1` of the fold with alpha, given
+    // the values a and b at points index and index + n/2 of layer `round`.
+    fn fold<E>(&self, round: usize, index: usize, a: E, b: E, alpha: E) -> E
+    where
+        E: ExtensionField<BaseField = F>;
+
+    // Maximum number of coefficients of the final polynomial on layer `round`
+    fn final_poly_len(&self, round: usize) -> usize;
+
+    // The coefficients of the polynomial of a codeword on layer `round`, cut
+    // to final_poly_len(round). Any further coefficients of a codeword of too
+    // high degree are dropped, so it no longer matches the folded queries.
+    fn final_poly<E>(&self, codeword: &[E], round: usize) -> Vec<E>
+    where
+        E: ExtensionField<BaseField = F>;
+
+    // The final polynomial at point `index` of layer `round`
+    fn eval_final_poly<E>(&self, coeffs: &[E], round: usize, index: usize) -> E
+    where
+        E: ExtensionField<BaseField = F>;
+
+    // A canonical encoding of the domain and the degree bound, for the transcript
+    fn description(&self) -> Vec<u8>;
+}
+
+// Domains that encode the coefficients of a UniPoly, in the monomial basis.
+// Circle FRI uses the circle basis instead, see FriProver::prove_coeffs.
+pub trait MonomialDomain<F: FriField>: FoldingDomain<F> {}
+
+// Folding stops once the last layer has 2^LOG_FINAL_LAYER_SIZE points, or
+// when one more round would leave a degree bound below 1, but happens at least
+// once. Smaller domains fold down to their degree bound.
+const LOG_FINAL_LAYER_SIZE: usize = 3;
+
+// The number of folding rounds over a domain of the given size, for polynomials
+// with at most max_degree coefficients
+pub(crate) fn num_folding_rounds(size: usize, max_degree: usize) -> usize {
+    let log_size = size.max(1).ilog2() as usize;
+    let log_degree = max_degree.max(1).ilog2() as usize;
+    log_size
+        .saturating_sub(LOG_FINAL_LAYER_SIZE)
+        .max(1)
+        .min(log_degree)
+}
+
+pub(crate) fn assert_can_fold<F: FriField>(domain: &impl FoldingDomain<F>) {
+    assert!(
+        domain.num_rounds() >= 1,
+        "The degree bound is too small to fold the domain"
+    );
+}
+
+// Cut to at most len coefficients, normalized like UniPoly (no trailing zeros).
+pub(crate) fn truncate_poly<E: ExtensionField>(mut coeffs: Vec<E>, len: usize) -> Vec<E> {
+    coeffs.truncate(len);
+    UniPoly::new(coeffs).coeffs
+}
+
+// A coset offset * <w> of a multiplicative subgroup. Folding pairs x with -x and
+// maps both to x^2, so layer i is the coset offset^(2^i) * <w^(2^i)>.
+pub struct SubgroupDomain<F: FriField> {
+    offset: F,
+    layers: Vec<Vec<F>>,
+    // Inverses of the points of each layer, for the fold
+    inverses: Vec<Vec<F>>,
+    two_inv: F,
+    expansion_factor: usize,
+}
+
+impl<F: FriField> SubgroupDomain<F> {
+    pub fn new(order: usize, expansion_factor: usize) -> Self {
+        Self::coset(F::one(), order, expansion_factor)
+    }
+
+    pub fn coset(offset: F, order: usize, expansion_factor: usize) -> Self {
+        assert!(order.is_power_of_two());
+        assert!(expansion_factor.is_power_of_two());
+
+        let elements = subgroup::<F>(order);
+        let offset_inv = offset.invert().unwrap();
+
+        // (offset w^j)^-1 = offset^-1 w^(n - j)
+        let mut layers = vec![elements.iter().map(|x| offset * x).collect::<Vec<F>>()];
+        let mut inverses = vec![(0..order)
+            .map(|j| offset_inv * elements[(order - j) % order])
+            .collect::<Vec<F>>()];
+        while layers.last().unwrap().len() > 1 {
+            let square_half = |layer: &Vec<F>| {
+                layer[..(layer.len() / 2)]
+                    .iter()
+                    .map(|x| x.square())
+                    .collect::<Vec<F>>()
+            };
+            let next_layer = square_half(layers.last().unwrap());
+            let next_inverses = square_half(inverses.last().unwrap());
+            layers.push(next_layer);
+            inverses.push(next_inverses);
+        }
+
+        let domain = Self {
+            offset,
+            layers,
+            inverses,
+            two_inv: F::from(2).invert().unwrap(),
+            expansion_factor,
+        };
+        assert_can_fold(&domain);
+        domain
+    }
+
+    pub fn offset(&self) -> F {
+        self.offset
+    }
+
+    pub fn layer(&self, round: usize) -> &[F] {
+        &self.layers[round]
+    }
+}
+
+impl<F: FriField> FoldingDomain<F> for SubgroupDomain<F> {
+    fn size(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    fn num_rounds(&self) -> usize {
+        num_folding_rounds(self.size(), self.size() / self.expansion_factor)
+    }
+
+    fn encode(&self, coeffs: &[F]) -> Vec<F> {
+        assert!(coeffs.len() <= self.size());
+
+        UniPoly::new(coeffs.to_vec()).eval_coset(self.offset, self.size())
+    }
+
+    fn fold<E>(&self, round: usize, index: usize, a: E, b: E, alpha: E) -> E
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        // f*(x^2) = 1/2 * ((f(x) + f(-x)) + alpha * x^-1 * (f(x) - f(-x)))
+        // (x, a), (-x, b) and (alpha, f*(x^2)) are colinear.
+        let x_inv = self.inverses[round][index];
+        (a + b + alpha * (a - b) * x_inv) * self.two_inv
+    }
+
+    fn final_poly_len(&self, round: usize) -> usize {
+        // Degree at most |layer| / expansion_factor
+        let len = self.layers[round].len();
+        (len / self.expansion_factor + 1).min(len)
+    }
+
+    fn final_poly<E>(&self, codeword: &[E], round: usize) -> Vec<E>
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        // Layer `round` is the coset of its first point.
+        let evals = Evaluations::new(codeword.to_vec(), self.layers[round][0]);
+        truncate_poly(evals.to_poly().coeffs, self.final_poly_len(round))
+    }
+
+    fn eval_final_poly<E>(&self, coeffs: &[E], round: usize, index: usize) -> E
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        UniPoly::new(coeffs.to_vec()).eval(E::from(self.layers[round][index]))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        let mut bytes = b"subgroup".to_vec();
+        bytes.extend_from_slice(&(self.size() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.expansion_factor as u64).to_le_bytes());
+        bytes.extend_from_slice(self.offset.to_repr().as_ref());
+        bytes
+    }
+}
+
+impl<F: FriField> MonomialDomain<F> for SubgroupDomain<F> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::PrimeField;
+    use pasta_curves::Fp;
+
+    #[test]
+    fn test_coset_domain() {
+        let offset = Fp::multiplicative_generator();
+        let domain = SubgroupDomain::coset(offset, 16, 2);
+        let coeffs = (0..8).map(|i| Fp::from(i as u64 + 1)).collect::<Vec<_>>();
+        let poly = UniPoly::new(coeffs.clone());
+
+        let codeword = domain.encode(&coeffs);
+        for (x, y) in domain.layer(0).iter().zip(codeword.iter()) {
+            assert_eq!(poly.eval(*x), *y);
+        }
+
+        // Folding each pair gives the evaluations of the folded polynomial on the next layer.
+        let alpha = Fp::from(1234);
+        let folded = (0..8)
+            .map(|j| domain.fold(0, j, codeword[j], codeword[j + 8], alpha))
+            .collect::<Vec<_>>();
+        let folded_poly = UniPoly::new(
+            coeffs
+                .chunks(2)
+                .map(|c| c[0] + alpha * c[1])
+                .collect::<Vec<_>>(),
+        );
+        for (x, y) in domain.layer(1).iter().zip(folded.iter()) {
+            assert_eq!(folded_poly.eval(*x), *y);
+        }
+        assert_eq!(domain.final_poly(&folded, 1), folded_poly.coeffs);
+    }
+
+    #[test]
+    fn test_layers() {
+        // Points j and j + n/2 of each layer are x and -x, and both square to point j
+        // of the next one.
+        let domain = SubgroupDomain::coset(Fp::from(7), 32, 2);
+        for round in 0..5 {
+            let (layer, next) = (domain.layer(round), domain.layer(round + 1));
+            let half = layer.len() / 2;
+            assert_eq!(next.len(), half);
+            for j in 0..half {
+                assert_eq!(layer[j + half], -layer[j]);
+                assert_eq!(next[j], layer[j].square());
+            }
+        }
+    }
+
+    #[test]
+    fn test_num_rounds() {
+        // (size, expansion factor, rounds)
+        for (size, expansion_factor, rounds) in [
+            (4, 2, 1),
+            (8, 2, 1),
+            (16, 2, 1),
+            (32, 2, 2),
+            (1024, 2, 7),
+            (1024, 8, 7),
+            (1024, 256, 2),
+        ] {
+            let domain = SubgroupDomain::<Fp>::new(size, expansion_factor);
+            assert_eq!(domain.num_rounds(), rounds);
+            assert!(domain.final_poly_len(rounds) >= 1);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The degree bound is too small to fold the domain")]
+    fn test_too_small_to_fold() {
+        SubgroupDomain::<Fp>::new(4, 4);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::domain::{
+    assert_can_fold, num_folding_rounds, truncate_poly, FoldingDomain, MonomialDomain,
+};
+use crate::ecfft::EcFftTree;
+use crate::field::{ExtensionField, FriField};
+use crate::fri_prover::FriProver;
+use crate::fri_verifier::FriVerifier;
+use crate::secp256k1::Secp256k1Base;
+
+// FRI over the ECFFT domains L_i, with the isogenies in place of squaring.
+//
+// A polynomial of degree < d on L_i is written as
+//   P(x) = (P_0(psi(x)) + x P_1(psi(x))) v(x)^(d/2 - 1)
+// with deg P_0, P_1 < d/2, and folds into P_0 + alpha P_1 of degree < d/2 on
+// L_{i+1}. As with squaring, each point of L_{i+1} has two preimages in L_i,
+// L_i[j] and L_i[j + |L_i| / 2], from which the folded value is computed.
+
+pub type EcFriProver = FriProver<Secp256k1Base, Secp256k1Base, EcFftDomain<Secp256k1Base>>;
+pub type EcFriVerifier = FriVerifier<Secp256k1Base, Secp256k1Base, EcFftDomain<Secp256k1Base>>;
+
+pub struct EcFftDomain<F: FriField> {
+    tree: EcFftTree<F>,
+    // Polynomials must have degree < max_degree
+    max_degree: usize,
+}
+
+impl<F: FriField> EcFftDomain<F> {
+    pub fn new(tree: EcFftTree<F>, max_degree: usize) -> Self {
+        assert!(max_degree.is_power_of_two() && max_degree < tree.layers[0].len());
+        let domain = Self { tree, max_degree };
+        assert_can_fold(&domain);
+        domain
+    }
+}
+
+impl EcFftDomain<Secp256k1Base> {
+    pub fn secp256k1(max_degree: usize, expansion_factor: usize) -> Self {
+        assert!(max_degree.is_power_of_two());
+        assert!(expansion_factor.is_power_of_two() && expansion_factor > 1);
+
+        let log_size = ((max_degree * expansion_factor) as f64).log2() as u32;
+        Self::new(EcFftTree::secp256k1(log_size), max_degree)
+    }
+}
+
+impl<F: FriField> FoldingDomain<F> for EcFftDomain<F> {
+    fn size(&self) -> usize {
+        self.tree.layers[0].len()
+    }
+
+    fn num_rounds(&self) -> usize {
+        num_folding_rounds(self.size(), self.max_degree)
+    }
+
+    fn encode(&self, coeffs: &[F]) -> Vec<F> {
+        assert!(coeffs.len() <= self.max_degree);
+        self.tree.enter(coeffs, 0)
+    }
+
+    fn fold<E>(&self, round: usize, index: usize, a: E, b: E, alpha: E) -> E
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let domain = &self.tree.layers[round];
+        let isogeny = &self.tree.isogenies[round];
+        let exp = [((self.max_degree >> round) / 2 - 1) as u64];
+        let (s0, s1) = (domain[index], domain[index + domain.len() / 2]);
+
+        let y0 = a * isogeny.v(s0).pow_vartime(exp).invert().unwrap();
+        let y1 = b * isogeny.v(s1).pow_vartime(exp).invert().unwrap();
+
+        // y0 = P_0 + s0 P_1, y1 = P_0 + s1 P_1
+        y0 + (alpha - E::from(s0)) * (y0 - y1) * (s0 - s1).invert().unwrap()
+    }
+
+    fn final_poly_len(&self, round: usize) -> usize {
+        self.max_degree >> round
+    }
+
+    fn final_poly<E>(&self, codeword: &[E], round: usize) -> Vec<E>
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let coeffs = self.tree.exit(codeword, round);
+        truncate_poly(coeffs, self.final_poly_len(round))
+    }
+
+    fn eval_final_poly<E>(&self, coeffs: &[E], round: usize, index: usize) -> E
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let x = self.tree.layers[round][index];
+        coeffs.iter().rev().fold(E::zero(), |acc, c| acc * x + *c)
+    }
+
+    fn description(&self) -> Vec<u8> {
+        // The isogenies are determined by the curve, so the initial points fix
+        // every layer.
+        let mut bytes = b"ecfft".to_vec();
+        bytes.extend_from_slice(&(self.size() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.max_degree as u64).to_le_bytes());
+        for x in &self.tree.layers[0] {
+            bytes.extend_from_slice(x.to_repr().as_ref());
+        }
+        bytes
+    }
+}
+
+// ENTER takes the coefficients in the monomial basis.
+impl<F: FriField> MonomialDomain<F> for EcFftDomain<F> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unipoly::UniPoly;
+    use crate::FriProof;
+    use ff::Field;
+    use merlin::Transcript;
+
+    type F = Secp256k1Base;
+
+    fn prove(max_degree: usize, num_coeffs: usize) -> (FriProof<F>, EcFriVerifier) {
+        let coeffs = (0..num_coeffs).map(|i| F::from(i as u64 + 1)).collect();
+        let poly = UniPoly::new(coeffs);
+
+        let prover = EcFriProver::with_domain(EcFftDomain::secp256k1(max_degree, 4), 4);
+        let proof = prover.prove_degree(&poly, &mut Transcript::new(b"test_ec_fri"));
+
+        let verifier = EcFriVerifier::with_domain(EcFftDomain::secp256k1(max_degree, 4), 4);
+        (proof, verifier)
+    }
+
+    #[test]
+    fn test_prove() {
+        for max_degree in [8, 16, 32] {
+            let (proof, verifier) = prove(max_degree, max_degree);
+            let com = &proof.initial_layer.cap.clone();
+            verifier.verify(&proof, com, &mut Transcript::new(b"test_ec_fri"));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Final polynomial has too high degree")]
+    fn test_too_high_degree() {
+        // Degree < 32 on a domain of the same size as for degree < 16
+        let coeffs = (0..32).map(|i| F::from(i as u64 + 1)).collect();
+        let prover = EcFriProver::with_domain(EcFftDomain::secp256k1(32, 2), 4);
+        let proof =
+            prover.prove_degree(&UniPoly::new(coeffs), &mut Transcript::new(b"test_ec_fri"));
+
+        let verifier = EcFriVerifier::with_domain(EcFftDomain::secp256k1(16, 4), 4);
+        let com = &proof.initial_layer.cap.clone();
+        verifier.verify(&proof, com, &mut Transcript::new(b"test_ec_fri"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid Merkle proof")]
+    fn test_tampered_proof() {
+        let (mut proof, verifier) = prove(16, 16);
+        let com = &proof.initial_layer.cap.clone();
+        let (a, _) = &mut proof.queries[0].openings[0];
+        a.leaf += F::one();
+        verifier.verify(&proof, com, &mut Transcript::new(b"test_ec_fri"));
+    }
+}
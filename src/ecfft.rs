@@ -0,0 +1,591 @@
+use crate::field::{ExtensionField, FriField};
+use crate::secp256k1::Secp256k1Base;
+use ff::Field;
+
+// Elliptic curve FFT (ECFFT, Ben-Sasson, Carmon, Kopparty and Levit).
+//
+// Fields like the secp256k1 base field have no large multiplicative subgroup of
+// order 2^k, but some elliptic curve over them has a point G of order 2^k.
+// The evaluation domain L_0 is the x-coordinates of a coset Q + <G>, and the
+// 2-isogeny phi_0 with kernel <2^(k-1) G> maps it two-to-one onto the
+// x-coordinates L_1 of phi_0(Q) + <phi_0(G)>, and so on down to a single point.
+// On x-coordinates each isogeny is the degree-2 rational map
+//   psi(x) = x + t / (x - x_0) = u(x) / v(x), with v(x) = x - x_0,
+// which plays the role that squaring plays for multiplicative subgroups.
+//
+// Points are ordered so that L_i[j] and L_i[j + |L_i| / 2] both map to L_{i+1}[j].
+
+// y^2 = x^3 + a x + b
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Curve<F> {
+    pub a: F,
+    pub b: F,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurvePoint<F> {
+    Infinity,
+    Affine(F, F),
+}
+
+impl<F: FriField> CurvePoint<F> {
+    pub fn x(&self) -> F {
+        match self {
+            CurvePoint::Infinity => panic!("the point at infinity has no x-coordinate"),
+            CurvePoint::Affine(x, _) => *x,
+        }
+    }
+}
+
+// A 2-isogeny given by Velu's formulas, from the kernel point (x_0, 0).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Isogeny<F> {
+    pub x0: F,
+    pub t: F,
+}
+
+impl<F: FriField> Curve<F> {
+    pub fn is_on_curve(&self, point: &CurvePoint<F>) -> bool {
+        match point {
+            CurvePoint::Infinity => true,
+            CurvePoint::Affine(x, y) => y.square() == x.square() * x + self.a * x + self.b,
+        }
+    }
+
+    pub fn neg(&self, point: &CurvePoint<F>) -> CurvePoint<F> {
+        match point {
+            CurvePoint::Infinity => CurvePoint::Infinity,
+            CurvePoint::Affine(x, y) => CurvePoint::Affine(*x, -*y),
+        }
+    }
+
+    pub fn add(&self, p: &CurvePoint<F>, q: &CurvePoint<F>) -> CurvePoint<F> {
+        let (x1, y1, x2, y2) = match (p, q) {
+            (CurvePoint::Infinity, _) => return *q,
+            (_, CurvePoint::Infinity) => return *p,
+            (CurvePoint::Affine(x1, y1), CurvePoint::Affine(x2, y2)) => (*x1, *y1, *x2, *y2),
+        };
+
+        let slope = if x1 == x2 {
+            if y1 != y2 || y1.is_zero_vartime() {
+                return CurvePoint::Infinity;
+            }
+            (x1.square() * F::from(3) + self.a) * y1.double().invert().unwrap()
+        } else {
+            (y2 - y1) * (x2 - x1).invert().unwrap()
+        };
+
+        let x3 = slope.square() - x1 - x2;
+        let y3 = slope * (x1 - x3) - y1;
+        CurvePoint::Affine(x3, y3)
+    }
+
+    pub fn double(&self, p: &CurvePoint<F>) -> CurvePoint<F> {
+        self.add(p, p)
+    }
+
+    pub fn mul(&self, p: &CurvePoint<F>, mut scalar: u64) -> CurvePoint<F> {
+        let mut result = CurvePoint::Infinity;
+        let mut base = *p;
+        while scalar > 0 {
+            if scalar & 1 == 1 {
+                result = self.add(&result, &base);
+            }
+            base = self.double(&base);
+            scalar >>= 1;
+        }
+
+        result
+    }
+
+    // The isogeny with the given kernel point of order 2, and its codomain.
+    pub fn isogeny(&self, kernel: &CurvePoint<F>) -> (Isogeny<F>, Curve<F>) {
+        let x0 = match kernel {
+            CurvePoint::Affine(x0, y0) if y0.is_zero_vartime() => *x0,
+            _ => panic!("the kernel must be a point of order 2"),
+        };
+
+        let t = x0.square() * F::from(3) + self.a;
+        let w = x0 * t;
+        let codomain = Curve {
+            a: self.a - t * F::from(5),
+            b: self.b - w * F::from(7),
+        };
+
+        (Isogeny { x0, t }, codomain)
+    }
+}
+
+impl<F: FriField> Isogeny<F> {
+    // The denominator v(x) = x - x_0 of psi
+    pub fn v(&self, x: F) -> F {
+        x - self.x0
+    }
+
+    pub fn map_x(&self, x: F) -> F {
+        x + self.t * self.v(x).invert().unwrap()
+    }
+
+    pub fn map_point(&self, p: &CurvePoint<F>) -> CurvePoint<F> {
+        match p {
+            CurvePoint::Affine(x, y) if *x != self.x0 => {
+                let v_inv = self.v(*x).invert().unwrap();
+                CurvePoint::Affine(
+                    *x + self.t * v_inv,
+                    *y * (F::one() - self.t * v_inv.square()),
+                )
+            }
+            // The kernel and infinity map to infinity.
+            _ => CurvePoint::Infinity,
+        }
+    }
+}
+
+// A curve over the secp256k1 base field with a point of order 2^16, found by the
+// `find_curve` test below: y^2 = (x - e_1)(x - e_2)(x - e_3) with e_1 = i,
+// e_2 = i^2 + 1 and e_3 = -e_1 - e_2 for i = SECP256K1_CURVE_SEED.
+pub const SECP256K1_LOG_ORDER: u32 = 16;
+const SECP256K1_CURVE_SEED: u64 = 13828;
+const SECP256K1_GENERATOR: [[u64; 4]; 2] = [
+    [
+        0x6502_9871_fa09_318b,
+        0xaa32_e9df_ceb5_eb81,
+        0x01d3_cbcf_43cf_dc36,
+        0x2222_e0c1_457c_7b4c,
+    ],
+    [
+        0x5fd0_96f4_5b28_bec5,
+        0x40a2_0eee_b344_9dfd,
+        0xe575_fa29_16a3_eade,
+        0xd72c_39ca_76ce_377d,
+    ],
+];
+
+fn curve_from_seed(seed: u64) -> (Curve<Secp256k1Base>, [Secp256k1Base; 3]) {
+    let e1 = Secp256k1Base::from(seed);
+    let e2 = Secp256k1Base::from(seed * seed + 1);
+    let e3 = -(e1 + e2);
+    let curve = Curve {
+        a: e1 * e2 + e1 * e3 + e2 * e3,
+        b: -(e1 * e2 * e3),
+    };
+
+    (curve, [e1, e2, e3])
+}
+
+// The curve and a generator of its subgroup of order 2^SECP256K1_LOG_ORDER
+pub fn secp256k1_curve() -> (Curve<Secp256k1Base>, CurvePoint<Secp256k1Base>) {
+    let (curve, _) = curve_from_seed(SECP256K1_CURVE_SEED);
+    let generator = CurvePoint::Affine(
+        Secp256k1Base::from_raw(SECP256K1_GENERATOR[0]),
+        Secp256k1Base::from_raw(SECP256K1_GENERATOR[1]),
+    );
+
+    (curve, generator)
+}
+
+pub struct EcFftTree<F: FriField> {
+    // L_0, ..., L_log_size, with |L_i| = 2^(log_size - i)
+    pub layers: Vec<Vec<F>>,
+    // psi_i maps L_i to L_{i+1}
+    pub isogenies: Vec<Isogeny<F>>,
+}
+
+impl<F: FriField> EcFftTree<F> {
+    // The tree over the x-coordinates of offset + <G>, where G generates the
+    // subgroup of order 2^log_size of <generator> (of order 2^log_order).
+    // 2 * offset must not be in <generator>, so that the x-coordinates are distinct.
+    pub fn new(
+        curve: Curve<F>,
+        generator: CurvePoint<F>,
+        log_order: u32,
+        offset: CurvePoint<F>,
+        log_size: u32,
+    ) -> Self {
+        assert!(log_size <= log_order);
+        assert!(curve.is_on_curve(&generator) && curve.is_on_curve(&offset));
+
+        let mut curve = curve;
+        let mut g = curve.mul(&generator, 1 << (log_order - log_size));
+
+        let mut layer = vec![];
+        let mut point = offset;
+        for _ in 0..(1usize << log_size) {
+            layer.push(point.x());
+            point = curve.add(&point, &g);
+        }
+
+        let mut layers = vec![layer];
+        let mut isogenies = vec![];
+        for i in 0..log_size {
+            let kernel = curve.mul(&g, 1 << (log_size - i - 1));
+            let (isogeny, codomain) = curve.isogeny(&kernel);
+
+            let layer = &layers[i as usize];
+            let next_layer = layer[..(layer.len() / 2)]
+                .iter()
+                .map(|x| isogeny.map_x(*x))
+                .collect();
+
+            g = isogeny.map_point(&g);
+            curve = codomain;
+            layers.push(next_layer);
+            isogenies.push(isogeny);
+        }
+
+        Self { layers, isogenies }
+    }
+
+    pub fn log_size(&self) -> usize {
+        self.isogenies.len()
+    }
+
+    // Evaluations on L_level of the polynomial with the given coefficients.
+    pub fn enter<E>(&self, coeffs: &[E], level: usize) -> Vec<E>
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let domain = &self.layers[level];
+        assert!(coeffs.len() <= domain.len());
+        let mut coeffs = coeffs.to_vec();
+        coeffs.resize(domain.len(), E::zero());
+
+        self.enter_rec(&coeffs, domain, level)
+    }
+
+    // Coefficients of the polynomial of degree < |L_level| with the given
+    // evaluations on L_level. This is quadratic, from multiplying by the vanishing
+    // polynomials of the halves, and only meant for small codewords.
+    pub fn exit<E>(&self, evals: &[E], level: usize) -> Vec<E>
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let domain = &self.layers[level];
+        assert!(evals.len() == domain.len());
+
+        self.exit_rec(evals, domain, level)
+    }
+
+    // Given the evaluations of a polynomial of degree < |from| on `from`,
+    // compute its evaluations on `to`.
+    // `from` and `to` must each be the x-coordinates of R + j H (in that order)
+    // for j < |from|, where psi_level identifies the points j and j + |from| / 2,
+    // e.g. the even and odd points of L_level.
+    pub fn extend<E>(&self, evals: &[E], from: &[F], to: &[F], level: usize) -> Vec<E>
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let n = from.len();
+        assert!(evals.len() == n && to.len() == n);
+        if n == 1 {
+            return evals.to_vec();
+        }
+
+        // P(X) = (P_0(psi(X)) + X P_1(psi(X))) v(X)^(n/2 - 1) with deg P_0, P_1 < n/2.
+        let isogeny = &self.isogenies[level];
+        let half = n / 2;
+        let exp = [(half - 1) as u64];
+
+        let mut evals_0 = vec![];
+        let mut evals_1 = vec![];
+        for j in 0..half {
+            let (s0, s1) = (from[j], from[j + half]);
+            let y0 = evals[j] * isogeny.v(s0).pow_vartime(exp).invert().unwrap();
+            let y1 = evals[j + half] * isogeny.v(s1).pow_vartime(exp).invert().unwrap();
+
+            let slope = (y0 - y1) * (s0 - s1).invert().unwrap();
+            evals_1.push(slope);
+            evals_0.push(y0 - slope * s0);
+        }
+
+        let next_from = from[..half]
+            .iter()
+            .map(|x| isogeny.map_x(*x))
+            .collect::<Vec<F>>();
+        let next_to = to[..half]
+            .iter()
+            .map(|x| isogeny.map_x(*x))
+            .collect::<Vec<F>>();
+        let evals_0 = self.extend(&evals_0, &next_from, &next_to, level + 1);
+        let evals_1 = self.extend(&evals_1, &next_from, &next_to, level + 1);
+
+        to.iter()
+            .enumerate()
+            .map(|(j, t)| {
+                (evals_0[j % half] + evals_1[j % half] * *t) * isogeny.v(*t).pow_vartime(exp)
+            })
+            .collect()
+    }
+
+    fn enter_rec<E>(&self, coeffs: &[E], domain: &[F], level: usize) -> Vec<E>
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let n = domain.len();
+        if n == 1 {
+            return coeffs.to_vec();
+        }
+
+        // P = low + X^(n/2) high, evaluated on the even points S and then extended
+        // to the odd points S'.
+        let half = n / 2;
+        let (evens, odds) = split(domain);
+        let (low, high) = coeffs.split_at(half);
+
+        let low_evens = self.enter_rec(low, &evens, level);
+        let high_evens = self.enter_rec(high, &evens, level);
+        let low_odds = self.extend(&low_evens, &evens, &odds, level);
+        let high_odds = self.extend(&high_evens, &evens, &odds, level);
+
+        let exp = [half as u64];
+        let mut evals = vec![];
+        for j in 0..half {
+            evals.push(low_evens[j] + high_evens[j] * evens[j].pow_vartime(exp));
+            evals.push(low_odds[j] + high_odds[j] * odds[j].pow_vartime(exp));
+        }
+
+        evals
+    }
+
+    fn exit_rec<E>(&self, evals: &[E], domain: &[F], level: usize) -> Vec<E>
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let n = domain.len();
+        if n == 1 {
+            return evals.to_vec();
+        }
+
+        // P = low + Z_S high, with Z_S the vanishing polynomial of the even points S
+        // and deg low, high < n/2. low agrees with P on S, and high on the odd points
+        // S' is (P - low) / Z_S there.
+        let (evens, odds) = split(domain);
+        let (evals_evens, evals_odds) = split(evals);
+
+        let low_odds = self.extend(&evals_evens, &evens, &odds, level);
+        let high_odds = odds
+            .iter()
+            .zip(evals_odds.iter().zip(low_odds.iter()))
+            .map(|(x, (p, low))| {
+                let z = evens.iter().fold(F::one(), |acc, s| acc * (*x - s));
+                (*p - low) * z.invert().unwrap()
+            })
+            .collect::<Vec<E>>();
+        let high_evens = self.extend(&high_odds, &odds, &evens, level);
+
+        let low = self.exit_rec(&evals_evens, &evens, level);
+        let high = self.exit_rec(&high_evens, &evens, level);
+
+        let mut z = vec![F::one()];
+        for s in &evens {
+            // z *= (X - s)
+            let mut next = vec![F::zero(); z.len() + 1];
+            for (i, c) in z.iter().enumerate() {
+                next[i + 1] += c;
+                next[i] -= *c * s;
+            }
+            z = next;
+        }
+
+        let mut coeffs = low;
+        coeffs.resize(n, E::zero());
+        for (i, h) in high.iter().enumerate() {
+            for (k, c) in z.iter().enumerate() {
+                coeffs[i + k] += *h * *c;
+            }
+        }
+        coeffs.truncate(n);
+
+        coeffs
+    }
+}
+
+impl EcFftTree<Secp256k1Base> {
+    // The tree of size 2^log_size over secp256k1_curve(), offset by the point with
+    // the smallest x-coordinate.
+    pub fn secp256k1(log_size: u32) -> Self {
+        let (curve, generator) = secp256k1_curve();
+
+        let offset = (1u64..)
+            .find_map(|x| {
+                let x = Secp256k1Base::from(x);
+                let y = Option::from((x.square() * x + curve.a * x + curve.b).sqrt());
+                y.map(|y| CurvePoint::Affine(x, y))
+            })
+            .unwrap();
+
+        Self::new(curve, generator, SECP256K1_LOG_ORDER, offset, log_size)
+    }
+}
+
+fn split<T: Copy>(values: &[T]) -> (Vec<T>, Vec<T>) {
+    let evens = values.iter().step_by(2).cloned().collect();
+    let odds = values.iter().skip(1).step_by(2).cloned().collect();
+    (evens, odds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+
+    type F = Secp256k1Base;
+
+    fn is_square(x: F) -> bool {
+        bool::from(x.sqrt().is_some())
+    }
+
+    // Depth of the longest chain of halvings from p, up to max_depth.
+    // On y^2 = (x - e_1)(x - e_2)(x - e_3), (x, y) is a double iff every x - e_i is
+    // a square, and its halves have x-coordinates x + r_1 r_2 + r_1 r_3 + r_2 r_3
+    // over the choices of square roots r_i of x - e_i.
+    fn halve(
+        curve: &Curve<F>,
+        roots: &[F; 3],
+        p: &CurvePoint<F>,
+        max_depth: u32,
+    ) -> (u32, CurvePoint<F>) {
+        if max_depth == 0 {
+            return (0, *p);
+        }
+        let x = p.x();
+        let diffs = roots.map(|e| x - e);
+        if !diffs.iter().all(|d| is_square(*d)) {
+            return (0, *p);
+        }
+        let r = diffs.map(|d| d.sqrt().unwrap());
+
+        let mut best = (0, *p);
+        for signs in [[1, 1, 1], [1, 1, -1], [1, -1, 1], [-1, 1, 1]] {
+            let r = [0, 1, 2].map(|i| if signs[i] == 1 { r[i] } else { -r[i] });
+            let half_x = x + r[0] * r[1] + r[0] * r[2] + r[1] * r[2];
+            let rhs = half_x.square() * half_x + curve.a * half_x + curve.b;
+            if !is_square(rhs) {
+                continue;
+            }
+            let half = CurvePoint::Affine(half_x, rhs.sqrt().unwrap());
+            let (depth, point) = halve(curve, roots, &half, max_depth - 1);
+            if depth + 1 > best.0 {
+                best = (depth + 1, point);
+            }
+            if best.0 == max_depth {
+                break;
+            }
+        }
+
+        best
+    }
+
+    // Search for a curve over the secp256k1 base field with a point of order 2^16.
+    // The curves have full rational 2-torsion, y^2 = (x - e_1)(x - e_2)(x - e_3)
+    // with e_3 = -e_1 - e_2, and we repeatedly halve a point of order 2.
+    // Takes a few minutes; run with `cargo test --release -- --ignored find_curve`.
+    #[test]
+    #[ignore]
+    fn find_curve() {
+        for seed in 1u64.. {
+            let (curve, roots) = curve_from_seed(seed);
+
+            for e in roots {
+                let order_two = CurvePoint::Affine(e, F::zero());
+                let (depth, point) = halve(&curve, &roots, &order_two, SECP256K1_LOG_ORDER - 1);
+                if depth == SECP256K1_LOG_ORDER - 1 {
+                    assert_eq!(seed, SECP256K1_CURVE_SEED);
+                    // Up to sign, which doesn't change the subgroup
+                    let (_, generator) = secp256k1_curve();
+                    assert!(point == generator || point == curve.neg(&generator));
+                    return;
+                }
+            }
+        }
+    }
+
+    fn eval(coeffs: &[F], x: F) -> F {
+        coeffs.iter().rev().fold(F::zero(), |acc, c| acc * x + c)
+    }
+
+    fn coeffs(n: usize) -> Vec<F> {
+        (0..n)
+            .map(|i| F::from(i as u64 * 31 + 7).square())
+            .collect()
+    }
+
+    #[test]
+    fn test_secp256k1_curve() {
+        let (curve, generator) = secp256k1_curve();
+        assert!(curve.is_on_curve(&generator));
+
+        // generator has order exactly 2^16
+        let half = curve.mul(&generator, 1 << (SECP256K1_LOG_ORDER - 1));
+        assert_ne!(half, CurvePoint::Infinity);
+        assert_eq!(curve.double(&half), CurvePoint::Infinity);
+    }
+
+    #[test]
+    fn test_isogeny() {
+        let (curve, generator) = secp256k1_curve();
+        let kernel = curve.mul(&generator, 1 << (SECP256K1_LOG_ORDER - 1));
+        let (isogeny, codomain) = curve.isogeny(&kernel);
+
+        // A homomorphism onto the codomain, with the given kernel
+        let p = curve.mul(&generator, 5);
+        let q = curve.mul(&generator, 1234);
+        let (p_image, q_image) = (isogeny.map_point(&p), isogeny.map_point(&q));
+        assert!(codomain.is_on_curve(&p_image));
+        assert_eq!(
+            isogeny.map_point(&curve.add(&p, &q)),
+            codomain.add(&p_image, &q_image)
+        );
+        assert_eq!(isogeny.map_point(&kernel), CurvePoint::Infinity);
+        assert_eq!(isogeny.map_point(&curve.add(&p, &kernel)), p_image);
+    }
+
+    #[test]
+    fn test_tree() {
+        let log_size = 6;
+        let tree = EcFftTree::secp256k1(log_size);
+        assert_eq!(tree.log_size(), log_size as usize);
+
+        for (i, layer) in tree.layers.iter().enumerate() {
+            assert_eq!(layer.len(), 1 << (log_size as usize - i));
+            for (j, x) in layer.iter().enumerate() {
+                assert!(!layer[..j].contains(x));
+            }
+        }
+        for (i, isogeny) in tree.isogenies.iter().enumerate() {
+            let (layer, next_layer) = (&tree.layers[i], &tree.layers[i + 1]);
+            let half = layer.len() / 2;
+            for j in 0..half {
+                assert_eq!(isogeny.map_x(layer[j]), next_layer[j]);
+                assert_eq!(isogeny.map_x(layer[j + half]), next_layer[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_enter_exit() {
+        let tree = EcFftTree::secp256k1(6);
+        for level in 0..tree.layers.len() {
+            let domain = &tree.layers[level];
+            let coeffs = coeffs(domain.len());
+
+            let evals = tree.enter(&coeffs, level);
+            for (x, y) in domain.iter().zip(evals.iter()) {
+                assert_eq!(*y, eval(&coeffs, *x));
+            }
+            assert_eq!(tree.exit(&evals, level), coeffs);
+        }
+    }
+
+    #[test]
+    fn test_extend() {
+        let tree = EcFftTree::secp256k1(6);
+        let (evens, odds) = split(&tree.layers[0]);
+        let coeffs = coeffs(evens.len());
+
+        let evals = evens.iter().map(|x| eval(&coeffs, *x)).collect::<Vec<_>>();
+        let expected = odds.iter().map(|x| eval(&coeffs, *x)).collect::<Vec<_>>();
+        assert_eq!(tree.extend(&evals, &evens, &odds, 0), expected);
+        assert_eq!(tree.extend(&expected, &odds, &evens, 0), evals);
+    }
+}
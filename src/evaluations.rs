@@ -0,0 +1,197 @@
+use crate::fft::ifft;
+use crate::field::{batch_invert, root_of_unity, subgroup, ExtensionField};
+use crate::unipoly::UniPoly;
+use ff::Field;
+use std::ops::{Add, Mul, Sub};
+
+// A polynomial of degree < n in evaluation (Lagrange) form: its values over the
+// coset offset * <w> of the subgroup of order n, in the order offset * w^i.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Evaluations<F>
+where
+    F: ExtensionField,
+{
+    pub evals: Vec<F>,
+    pub offset: F::BaseField,
+}
+
+impl<F> Evaluations<F>
+where
+    F: ExtensionField,
+{
+    pub fn new(evals: Vec<F>, offset: F::BaseField) -> Self {
+        assert!(evals.len().is_power_of_two());
+        Self { evals, offset }
+    }
+
+    // Evaluations over the subgroup of order n itself
+    pub fn over_subgroup(evals: Vec<F>) -> Self {
+        Self::new(evals, F::BaseField::one())
+    }
+
+    pub fn from_poly(poly: &UniPoly<F>, offset: F::BaseField, n: usize) -> Self {
+        Self::new(poly.eval_coset(offset, n), offset)
+    }
+
+    pub fn len(&self) -> usize {
+        self.evals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.evals.is_empty()
+    }
+
+    pub fn domain(&self) -> Vec<F::BaseField> {
+        subgroup::<F::BaseField>(self.len())
+            .into_iter()
+            .map(|x| self.offset * x)
+            .collect()
+    }
+
+    pub fn to_poly(&self) -> UniPoly<F> {
+        // Interpolating over the subgroup gives the coefficients of f(offset X).
+        let coeffs = ifft(&subgroup(self.len()), &self.evals);
+
+        let offset_inv = self.offset.invert().unwrap();
+        let mut offset_inv_pow = F::BaseField::one();
+        let mut unscaled = vec![];
+        for c in coeffs {
+            unscaled.push(c * offset_inv_pow);
+            offset_inv_pow *= offset_inv;
+        }
+
+        UniPoly::new(unscaled)
+    }
+
+    // Barycentric evaluation at any point. Over the coset, Z(X) = X^n - offset^n and
+    //   f(z) = Z(z) / (n offset^n) * sum_i f(x_i) x_i / (z - x_i).
+    pub fn eval(&self, z: F) -> F {
+        let n = self.len();
+        let generator = root_of_unity::<F::BaseField>(n);
+
+        // z - x_i for x_i = offset * w^i, all inverted at once
+        let mut denominators = Vec::with_capacity(n);
+        let mut x = self.offset;
+        for y in &self.evals {
+            let d = z - F::from(x);
+            if d.is_zero_vartime() {
+                return *y;
+            }
+            denominators.push(d);
+            x *= generator;
+        }
+        batch_invert(&mut denominators);
+
+        let offset_n = self.offset.pow_vartime([n as u64]);
+        let vanishing = z.pow_vartime([n as u64]) - F::from(offset_n);
+        let scale = (F::BaseField::from(n as u64) * offset_n).invert().unwrap();
+
+        let mut x = self.offset;
+        let mut sum = F::zero();
+        for (y, d_inv) in self.evals.iter().zip(denominators) {
+            sum += *y * d_inv * x;
+            x *= generator;
+        }
+
+        vanishing * sum * scale
+    }
+
+    // Low-degree extension onto the coset offset * <w'> of the subgroup of order n.
+    pub fn lde(&self, offset: F::BaseField, n: usize) -> Self {
+        assert!(n >= self.len());
+        Self::from_poly(&self.to_poly(), offset, n)
+    }
+
+    fn assert_same_domain(&self, rhs: &Self) {
+        assert!(
+            self.len() == rhs.len() && self.offset == rhs.offset,
+            "Evaluations over different domains"
+        );
+    }
+}
+
+macro_rules! impl_pointwise_op {
+    ($trait:ident, $method:ident) => {
+        impl<'a, F: ExtensionField> $trait<&'a Evaluations<F>> for &'a Evaluations<F> {
+            type Output = Evaluations<F>;
+
+            fn $method(self, rhs: &'a Evaluations<F>) -> Evaluations<F> {
+                self.assert_same_domain(rhs);
+                let evals = self
+                    .evals
+                    .iter()
+                    .zip(rhs.evals.iter())
+                    .map(|(a, b)| a.$method(b))
+                    .collect();
+
+                Evaluations::new(evals, self.offset)
+            }
+        }
+
+        impl<F: ExtensionField> $trait for Evaluations<F> {
+            type Output = Evaluations<F>;
+
+            fn $method(self, rhs: Self) -> Evaluations<F> {
+                (&self).$method(&rhs)
+            }
+        }
+    };
+}
+
+// The product of two polynomials of degree < n/2 is still determined by its
+// evaluations over n points; beyond that Mul wraps around modulo Z(X).
+impl_pointwise_op!(Add, add);
+impl_pointwise_op!(Sub, sub);
+impl_pointwise_op!(Mul, mul);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasta_curves::Fp;
+
+    fn poly(len: usize, seed: u64) -> UniPoly<Fp> {
+        UniPoly::new((0..len).map(|i| Fp::from(i as u64 * seed + 3)).collect())
+    }
+
+    #[test]
+    fn test_conversions() {
+        for offset in [Fp::one(), Fp::from(5)] {
+            let a = poly(16, 7);
+            let evals = Evaluations::from_poly(&a, offset, 16);
+            for (x, y) in evals.domain().iter().zip(evals.evals.iter()) {
+                assert_eq!(a.eval(*x), *y);
+            }
+            assert_eq!(evals.to_poly(), a);
+
+            // Out-of-domain and in-domain points
+            for z in [Fp::from(123456), evals.domain()[3]] {
+                assert_eq!(evals.eval(z), a.eval(z));
+            }
+
+            let extended = evals.lde(Fp::from(7), 64);
+            assert_eq!(extended.len(), 64);
+            assert_eq!(extended.to_poly(), a);
+        }
+    }
+
+    #[test]
+    fn test_pointwise() {
+        let offset = Fp::from(5);
+        let (a, b) = (poly(8, 7), poly(8, 11));
+        let a_evals = Evaluations::from_poly(&a, offset, 16);
+        let b_evals = Evaluations::from_poly(&b, offset, 16);
+
+        let z = Fp::from(99);
+        assert_eq!((&a_evals + &b_evals).eval(z), a.eval(z) + b.eval(z));
+        assert_eq!((&a_evals - &b_evals).eval(z), a.eval(z) - b.eval(z));
+        assert_eq!((&a_evals * &b_evals).eval(z), a.eval(z) * b.eval(z));
+    }
+
+    #[test]
+    #[should_panic(expected = "Evaluations over different domains")]
+    fn test_different_domains() {
+        let a = Evaluations::from_poly(&poly(8, 7), Fp::one(), 16);
+        let b = Evaluations::from_poly(&poly(8, 7), Fp::from(5), 16);
+        let _ = &a + &b;
+    }
+}
@@ -0,0 +1,272 @@
+use crate::field::{
+    limbs_shr, prime_power_minus_one, sqrt_tonelli_shanks, ExtensionField, FriField,
+};
+use ff::Field;
+use rand_core::RngCore;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+// A base field with a degree D extension F[X] / (X^D - sum_i r_i X^i).
+pub trait Extendable<const D: usize>: FriField {
+    // The characteristic of the field, which must fit in a u64.
+    const CHARACTERISTIC: u64;
+
+    // The coefficients r_i of the reduction X^D = sum_i r_i X^i.
+    // The polynomial X^D - sum_i r_i X^i must be irreducible.
+    fn reduction() -> [Self; D];
+}
+
+// An element of F[X] / (X^D - sum_i r_i X^i), as its coefficients in 1, X, ..., X^(D-1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Extension<F, const D: usize>(pub [F; D]);
+
+impl<F: Extendable<D>, const D: usize> Extension<F, D> {
+    fn add(&self, rhs: &Self) -> Self {
+        let mut coeffs = self.0;
+        for (a, b) in coeffs.iter_mut().zip(rhs.0.iter()) {
+            *a += b;
+        }
+        Self(coeffs)
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        let mut coeffs = self.0;
+        for (a, b) in coeffs.iter_mut().zip(rhs.0.iter()) {
+            *a -= b;
+        }
+        Self(coeffs)
+    }
+
+    fn neg(&self) -> Self {
+        Self(self.0.map(|a| -a))
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        // The product of degree up to 2D - 2, as its coefficients below X^D and
+        // those of X^D, ..., X^(2D - 2), on the stack.
+        let mut low = [F::zero(); D];
+        let mut high = [F::zero(); D];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in rhs.0.iter().enumerate() {
+                let k = i + j;
+                if k < D {
+                    low[k] += *a * b;
+                } else {
+                    high[k - D] += *a * b;
+                }
+            }
+        }
+
+        // X^k = X^(k - D) * sum_i r_i X^i, from the top down
+        let reduction = F::reduction();
+        for k in (D..(2 * D - 1)).rev() {
+            let top = high[k - D];
+            for (i, r) in reduction.iter().enumerate() {
+                let k = k - D + i;
+                if k < D {
+                    low[k] += top * r;
+                } else {
+                    high[k - D] += top * r;
+                }
+            }
+        }
+
+        Self(low)
+    }
+
+    fn mul_base(&self, rhs: &F) -> Self {
+        Self(self.0.map(|a| a * rhs))
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> Default for Extension<F, D> {
+    fn default() -> Self {
+        Self([F::zero(); D])
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> From<F> for Extension<F, D> {
+    fn from(value: F) -> Self {
+        let mut coeffs = [F::zero(); D];
+        coeffs[0] = value;
+        Self(coeffs)
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> ConditionallySelectable for Extension<F, D> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut coeffs = a.0;
+        for (c, b) in coeffs.iter_mut().zip(b.0.iter()) {
+            *c = F::conditional_select(c, b, choice);
+        }
+        Self(coeffs)
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> ConstantTimeEq for Extension<F, D> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .fold(Choice::from(1), |acc, (a, b)| acc & a.ct_eq(b))
+    }
+}
+
+macro_rules! impl_binop {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $rhs:ty, $impl:ident) => {
+        impl<F: Extendable<D>, const D: usize> $trait<$rhs> for Extension<F, D> {
+            type Output = Self;
+
+            fn $method(self, rhs: $rhs) -> Self {
+                Extension::$impl(&self, &rhs)
+            }
+        }
+
+        impl<'a, F: Extendable<D>, const D: usize> $trait<&'a $rhs> for Extension<F, D> {
+            type Output = Self;
+
+            fn $method(self, rhs: &'a $rhs) -> Self {
+                Extension::$impl(&self, rhs)
+            }
+        }
+
+        impl<F: Extendable<D>, const D: usize> $assign_trait<$rhs> for Extension<F, D> {
+            fn $assign_method(&mut self, rhs: $rhs) {
+                *self = Extension::$impl(self, &rhs);
+            }
+        }
+
+        impl<'a, F: Extendable<D>, const D: usize> $assign_trait<&'a $rhs> for Extension<F, D> {
+            fn $assign_method(&mut self, rhs: &'a $rhs) {
+                *self = Extension::$impl(self, rhs);
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, AddAssign, add_assign, Self, add);
+impl_binop!(Sub, sub, SubAssign, sub_assign, Self, sub);
+impl_binop!(Mul, mul, MulAssign, mul_assign, Self, mul);
+impl_binop!(Mul, mul, MulAssign, mul_assign, F, mul_base);
+
+impl<F: Extendable<D>, const D: usize> Neg for Extension<F, D> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Extension::neg(&self)
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> Field for Extension<F, D> {
+    fn random(mut rng: impl RngCore) -> Self {
+        let mut coeffs = [F::zero(); D];
+        for c in coeffs.iter_mut() {
+            *c = F::random(&mut rng);
+        }
+        Self(coeffs)
+    }
+
+    fn zero() -> Self {
+        Self([F::zero(); D])
+    }
+
+    fn one() -> Self {
+        Self::from(F::one())
+    }
+
+    fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        // Solve self * y = 1 as a linear system over F.
+        // Column j of the matrix is self * X^j.
+        let x = Self(std::array::from_fn(|i| {
+            if i == 1 {
+                F::one()
+            } else {
+                F::zero()
+            }
+        }));
+        let mut columns = vec![];
+        let mut x_pow = Self::one();
+        for _ in 0..D {
+            columns.push(self.mul(&x_pow));
+            x_pow = x_pow.mul(&x);
+        }
+
+        // Augmented rows [M | e_0]
+        let mut rows = (0..D)
+            .map(|i| {
+                let mut row = columns.iter().map(|col| col.0[i]).collect::<Vec<F>>();
+                row.push(if i == 0 { F::one() } else { F::zero() });
+                row
+            })
+            .collect::<Vec<Vec<F>>>();
+
+        for col in 0..D {
+            let pivot = match (col..D).find(|&row| !rows[row][col].is_zero_vartime()) {
+                Some(pivot) => pivot,
+                None => return CtOption::new(Self::zero(), Choice::from(0)),
+            };
+            rows.swap(col, pivot);
+
+            let inv = rows[col][col].invert().unwrap();
+            for val in rows[col].iter_mut() {
+                *val *= inv;
+            }
+
+            let pivot_row = rows[col].clone();
+            for (i, row) in rows.iter_mut().enumerate() {
+                if i != col && !row[col].is_zero_vartime() {
+                    let factor = row[col];
+                    for (val, pivot_val) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                        *val -= *pivot_val * factor;
+                    }
+                }
+            }
+        }
+
+        CtOption::new(Self(std::array::from_fn(|i| rows[i][D])), Choice::from(1))
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        let q_minus_one = prime_power_minus_one(F::CHARACTERISTIC, D);
+        let half = limbs_shr(&q_minus_one, 1);
+
+        // X + k is a non-residue for some small k
+        let non_residue = (0u64..)
+            .map(|k| {
+                let mut coeffs = [F::zero(); D];
+                coeffs[0] = F::from(k);
+                if D > 1 {
+                    coeffs[1] = F::one();
+                }
+                Self(coeffs)
+            })
+            .find(|z| !z.is_zero_vartime() && z.pow_vartime(&half) != Self::one())
+            .unwrap();
+
+        sqrt_tonelli_shanks(self, &q_minus_one, non_residue)
+    }
+}
+
+impl<F: Extendable<D>, const D: usize> ExtensionField for Extension<F, D> {
+    type BaseField = F;
+    const DEGREE: usize = D;
+
+    fn from_base_slice(coeffs: &[F]) -> Self {
+        assert!(coeffs.len() == D);
+        let mut result = [F::zero(); D];
+        result.copy_from_slice(coeffs);
+        Self(result)
+    }
+
+    fn to_base_vec(&self) -> Vec<F> {
+        self.0.to_vec()
+    }
+}
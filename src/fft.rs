@@ -1,9 +1,9 @@
-use pasta_curves::{arithmetic::FieldExt, group::ff::PrimeField};
-use std::vec;
+use crate::field::ExtensionField;
+use ff::Field;
 
-pub fn fft<F>(coeffs: &[F], domain: &[F]) -> Vec<F>
+pub fn fft<F>(coeffs: &[F], domain: &[F::BaseField]) -> Vec<F>
 where
-    F: FieldExt<Repr = [u8; 32]>,
+    F: ExtensionField,
 {
     assert!(coeffs.len() == domain.len());
     if coeffs.len() == 1 {
@@ -12,57 +12,47 @@ where
 
     // TODO: Just borrow the values
     // Split into evens and odds
-    let L = coeffs
+    let evens = coeffs
         .iter()
         .enumerate()
         .filter(|(i, _)| i % 2 == 0)
         .map(|(_, x)| *x)
         .collect::<Vec<F>>();
 
-    let R = coeffs
+    let odds = coeffs
         .iter()
         .enumerate()
         .filter(|(i, _)| i % 2 == 1)
         .map(|(_, x)| *x)
         .collect::<Vec<F>>();
 
-    // Square the domain values
-    /*
-    let domain_squared: Vec<F> = domain
+    // The second half of the domain is the negation of the first, so squaring the
+    // first half gives the next domain.
+    let domain_squared = domain[..(domain.len() / 2)]
         .iter()
-        .enumerate()
-        .filter(|(i, _)| i % 2 == 0)
-        .map(|(_, x)| *x)
-        .collect();
-     */
+        .map(|x| x.square())
+        .collect::<Vec<_>>();
 
-    let mut domain_squared = vec![];
-    domain.iter().map(|x| x.square()).for_each(|x| {
-        if !domain_squared.contains(&x) {
-            domain_squared.push(x);
-        }
-    });
-
-    let fft_e = fft(&L, &domain_squared);
-    let fft_o = fft(&R, &domain_squared);
+    let fft_e = fft(&evens, &domain_squared);
+    let fft_o = fft(&odds, &domain_squared);
 
-    let mut evals_L = vec![];
-    let mut evals_R = vec![];
+    let mut evals_l = vec![];
+    let mut evals_r = vec![];
     for i in 0..(coeffs.len() / 2) {
         // We can use the previous evaluations to create a list of evaluations
         // of the domain
-        evals_L.push(fft_e[i] + fft_o[i] * domain[i]);
-        evals_R.push(fft_e[i] - fft_o[i] * domain[i]);
+        evals_l.push(fft_e[i] + fft_o[i] * domain[i]);
+        evals_r.push(fft_e[i] - fft_o[i] * domain[i]);
     }
 
-    evals_L.extend(evals_R);
-    return evals_L;
+    evals_l.extend(evals_r);
+    evals_l
 }
 
-pub fn ifft<F: PrimeField<Repr = [u8; 32]> + FieldExt>(domain: &[F], evals: &[F]) -> Vec<F> {
+pub fn ifft<F: ExtensionField>(domain: &[F::BaseField], evals: &[F]) -> Vec<F> {
     let mut coeffs = vec![];
-    let len_mod_inv = F::from(domain.len() as u64).invert().unwrap();
-    let vals = fft(&evals, &domain);
+    let len_mod_inv = F::BaseField::from(domain.len() as u64).invert().unwrap();
+    let vals = fft(evals, domain);
 
     coeffs.push(vals[0] * len_mod_inv);
     for val in vals[1..].iter().rev() {
@@ -74,9 +64,10 @@ pub fn ifft<F: PrimeField<Repr = [u8; 32]> + FieldExt>(domain: &[F], evals: &[F]
 
 #[cfg(test)]
 mod tests {
-    use pasta_curves::Fp;
-
     use super::*;
+    use crate::field::subgroup;
+    use ff::Field;
+    use pasta_curves::Fp;
     #[test]
     fn test_fft_ifft() {
         // f(x) = 1 + 2x + 3x^2 + 4x^3
@@ -91,32 +82,18 @@ mod tests {
             Fp::from(81),
         ];
 
-        let mut domain = vec![];
-
-        let root_of_unity = Fp::root_of_unity();
-
         let subgroup_order = (coeffs.len() * 2).next_power_of_two();
 
         coeffs.resize(subgroup_order, Fp::zero());
 
-        // Generator for the subgroup with order _subgroup_order_ in the field
-        let generator = root_of_unity.pow(&[
-            2u32.pow(32 - ((subgroup_order as f64).log2() as u32)) as u64,
-            0,
-            0,
-            0,
-        ]);
-
-        for i in 0..(subgroup_order) {
-            domain.push(generator.pow(&[i as u64, 0, 0, 0]));
-        }
+        let domain = subgroup::<Fp>(subgroup_order);
 
         let mut expected_evals = vec![];
 
         for w in &domain {
             let mut eval = Fp::zero();
-            for (i, coeff) in (&coeffs).iter().enumerate() {
-                eval += coeff * w.pow(&[i as u64, 0, 0, 0]);
+            for (i, coeff) in coeffs.iter().enumerate() {
+                eval += coeff * w.pow_vartime([i as u64]);
             }
             expected_evals.push(eval);
         }
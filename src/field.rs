@@ -0,0 +1,391 @@
+use ff::{Field, PrimeField};
+use std::ops::Mul;
+use subtle::{Choice, CtOption};
+
+// The field interface the FRI prover and verifier need on top of ff::PrimeField:
+// a two-adic root of unity (PrimeField::root_of_unity and PrimeField::S),
+// a byte encoding of any length (PrimeField::Repr) and a way to map hash
+// or transcript output to a field element.
+pub trait FriField: PrimeField {
+    // Number of bytes in the encoding of a field element.
+    fn repr_len() -> usize {
+        Self::Repr::default().as_ref().len()
+    }
+
+    // Number of uniformly random bytes to squeeze from a transcript per challenge.
+    // The default leaves a statistical distance of at most 2^-128 from uniform.
+    fn uniform_bytes_len() -> usize {
+        Self::repr_len() + 16
+    }
+
+    // Map random bytes to a field element by reducing them as a big-endian integer.
+    fn from_uniform_bytes(bytes: &[u8]) -> Self {
+        let base = Self::from(256);
+        bytes
+            .iter()
+            .fold(Self::zero(), |acc, b| acc * base + Self::from(*b as u64))
+    }
+}
+
+macro_rules! impl_fri_field_wide {
+    ($field:ty) => {
+        impl FriField for $field {
+            fn uniform_bytes_len() -> usize {
+                64
+            }
+
+            fn from_uniform_bytes(bytes: &[u8]) -> Self {
+                use pasta_curves::arithmetic::FieldExt;
+
+                assert!(bytes.len() <= 64);
+                let mut wide = [0u8; 64];
+                wide[..bytes.len()].copy_from_slice(bytes);
+                Self::from_bytes_wide(&wide)
+            }
+        }
+    };
+}
+
+impl_fri_field_wide!(pasta_curves::Fp);
+impl_fri_field_wide!(pasta_curves::Fq);
+
+// Field elements that appear in codewords, Merkle trees, transcripts and proofs:
+// either a FriField itself or an extension of one, as used for challenges
+// and folded codewords over small fields.
+pub trait ExtensionField:
+    Field + From<Self::BaseField> + Mul<Self::BaseField, Output = Self>
+{
+    type BaseField: FriField;
+    const DEGREE: usize;
+
+    fn from_base_slice(coeffs: &[Self::BaseField]) -> Self;
+
+    fn to_base_vec(&self) -> Vec<Self::BaseField>;
+
+    fn num_bytes() -> usize {
+        Self::DEGREE * Self::BaseField::repr_len()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_base_vec()
+            .iter()
+            .flat_map(|coeff| coeff.to_repr().as_ref().to_vec())
+            .collect()
+    }
+
+    // Returns None unless every coefficient is canonically encoded.
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::num_bytes() {
+            return None;
+        }
+
+        let coeffs = bytes
+            .chunks(Self::BaseField::repr_len())
+            .map(|chunk| {
+                let mut repr = <Self::BaseField as PrimeField>::Repr::default();
+                repr.as_mut().copy_from_slice(chunk);
+                Option::from(Self::BaseField::from_repr(repr))
+            })
+            .collect::<Option<Vec<Self::BaseField>>>()?;
+
+        Some(Self::from_base_slice(&coeffs))
+    }
+
+    fn num_random_bytes() -> usize {
+        Self::DEGREE * Self::BaseField::uniform_bytes_len()
+    }
+
+    fn from_random_bytes(bytes: &[u8]) -> Self {
+        assert!(bytes.len() == Self::num_random_bytes());
+
+        let coeffs = bytes
+            .chunks(Self::BaseField::uniform_bytes_len())
+            .map(Self::BaseField::from_uniform_bytes)
+            .collect::<Vec<Self::BaseField>>();
+
+        Self::from_base_slice(&coeffs)
+    }
+}
+
+impl<F: FriField> ExtensionField for F {
+    type BaseField = F;
+    const DEGREE: usize = 1;
+
+    fn from_base_slice(coeffs: &[F]) -> Self {
+        assert!(coeffs.len() == 1);
+        coeffs[0]
+    }
+
+    fn to_base_vec(&self) -> Vec<F> {
+        vec![*self]
+    }
+}
+
+// Operator and constant-time trait impls for a small prime field wrapping a
+// canonical integer, in terms of its inherent add, sub, mul and neg.
+macro_rules! impl_field_ops {
+    ($field:ident) => {
+        impl subtle::ConditionallySelectable for $field {
+            fn conditional_select(a: &Self, b: &Self, choice: subtle::Choice) -> Self {
+                Self(subtle::ConditionallySelectable::conditional_select(
+                    &a.0, &b.0, choice,
+                ))
+            }
+        }
+
+        impl subtle::ConstantTimeEq for $field {
+            fn ct_eq(&self, other: &Self) -> subtle::Choice {
+                subtle::ConstantTimeEq::ct_eq(&self.0, &other.0)
+            }
+        }
+
+        impl_field_ops!($field, Add, add, AddAssign, add_assign);
+        impl_field_ops!($field, Sub, sub, SubAssign, sub_assign);
+        impl_field_ops!($field, Mul, mul, MulAssign, mul_assign);
+
+        impl std::ops::Neg for $field {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $field::neg(&self)
+            }
+        }
+    };
+    ($field:ident, $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident) => {
+        impl std::ops::$trait for $field {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                $field::$method(&self, &rhs)
+            }
+        }
+
+        impl<'a> std::ops::$trait<&'a $field> for $field {
+            type Output = Self;
+
+            fn $method(self, rhs: &'a Self) -> Self {
+                $field::$method(&self, rhs)
+            }
+        }
+
+        impl std::ops::$assign_trait for $field {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = $field::$method(self, &rhs);
+            }
+        }
+
+        impl<'a> std::ops::$assign_trait<&'a $field> for $field {
+            fn $assign_method(&mut self, rhs: &'a Self) {
+                *self = $field::$method(self, rhs);
+            }
+        }
+    };
+}
+
+pub(crate) use impl_field_ops;
+
+// Square root by Tonelli-Shanks in a field of order q, given the little-endian
+// limbs of q - 1 and a quadratic non-residue.
+pub(crate) fn sqrt_tonelli_shanks<F: Field>(
+    a: &F,
+    q_minus_one: &[u64],
+    non_residue: F,
+) -> CtOption<F> {
+    if a.is_zero_vartime() {
+        return CtOption::new(F::zero(), Choice::from(1));
+    }
+
+    // q - 1 = 2^s * t with t odd
+    let s = limbs_trailing_zeros(q_minus_one);
+    let t = limbs_shr(q_minus_one, s);
+
+    let mut m = s;
+    let mut c = non_residue.pow_vartime(&t);
+    let mut x = a.pow_vartime(limbs_shr(&limbs_add_one(&t), 1));
+    let mut b = a.pow_vartime(&t);
+
+    while b != F::one() {
+        // Least i with b^(2^i) = 1
+        let mut i = 0;
+        let mut b_pow = b;
+        while b_pow != F::one() {
+            b_pow = b_pow.square();
+            i += 1;
+            if i == m {
+                // a is not a square
+                return CtOption::new(F::zero(), Choice::from(0));
+            }
+        }
+
+        let mut d = c;
+        for _ in 0..(m - i - 1) {
+            d = d.square();
+        }
+
+        m = i;
+        c = d.square();
+        x *= d;
+        b *= c;
+    }
+
+    CtOption::new(x, Choice::from(1))
+}
+
+// Little-endian limbs of p^k - 1.
+pub(crate) fn prime_power_minus_one(p: u64, k: usize) -> Vec<u64> {
+    let mut limbs = vec![1u64];
+    for _ in 0..k {
+        let mut carry = 0u128;
+        for limb in limbs.iter_mut() {
+            let prod = (*limb as u128) * (p as u128) + carry;
+            *limb = prod as u64;
+            carry = prod >> 64;
+        }
+        if carry != 0 {
+            limbs.push(carry as u64);
+        }
+    }
+
+    // p^k is odd, so subtracting one never borrows.
+    limbs[0] -= 1;
+    limbs
+}
+
+fn limbs_trailing_zeros(limbs: &[u64]) -> u32 {
+    let mut zeros = 0;
+    for limb in limbs {
+        if *limb == 0 {
+            zeros += 64;
+        } else {
+            return zeros + limb.trailing_zeros();
+        }
+    }
+
+    zeros
+}
+
+pub(crate) fn limbs_shr(limbs: &[u64], shift: u32) -> Vec<u64> {
+    let words = (shift / 64) as usize;
+    let bits = shift % 64;
+    (words..limbs.len())
+        .map(|i| {
+            let hi = if bits > 0 && i + 1 < limbs.len() {
+                limbs[i + 1] << (64 - bits)
+            } else {
+                0
+            };
+            (limbs[i] >> bits) | hi
+        })
+        .collect()
+}
+
+fn limbs_add_one(limbs: &[u64]) -> Vec<u64> {
+    let mut result = limbs.to_vec();
+    for limb in result.iter_mut() {
+        let (sum, overflow) = limb.overflowing_add(1);
+        *limb = sum;
+        if !overflow {
+            return result;
+        }
+    }
+
+    result.push(1);
+    result
+}
+
+// Invert nonzero values in place with a single field inversion (Montgomery's
+// trick): the prefix products are inverted once and unwound from the end.
+pub(crate) fn batch_invert<F: Field>(values: &mut [F]) {
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = F::one();
+    for v in values.iter() {
+        prefix.push(acc);
+        acc *= v;
+    }
+
+    let mut inv = acc.invert().expect("Zero in batch inversion");
+    for (v, p) in values.iter_mut().zip(prefix).rev() {
+        let next = inv * *v;
+        *v = inv * p;
+        inv = next;
+    }
+}
+
+// Generator of the multiplicative subgroup of the given order.
+pub fn root_of_unity<F: FriField>(order: usize) -> F {
+    assert!(order.is_power_of_two());
+    let log_order = order.trailing_zeros();
+    assert!(
+        log_order <= F::S,
+        "the field has no subgroup of order {}",
+        order
+    );
+
+    let mut root = F::root_of_unity();
+    for _ in log_order..F::S {
+        root = root.square();
+    }
+
+    root
+}
+
+// The subgroup of the given order, as consecutive powers of its generator.
+pub fn subgroup<F: FriField>(order: usize) -> Vec<F> {
+    let generator = root_of_unity::<F>(order);
+    let mut domain = Vec::with_capacity(order);
+    let mut x = F::one();
+    for _ in 0..order {
+        domain.push(x);
+        x *= generator;
+    }
+
+    domain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasta_curves::{Fp, Fq};
+
+    fn test_root_of_unity<F: FriField>() {
+        for log_order in [0, 1, 5, F::S] {
+            let root = root_of_unity::<F>(1 << log_order);
+            assert_eq!(root.pow_vartime([1u64 << log_order]), F::one());
+            if log_order > 0 {
+                assert_ne!(root.pow_vartime([1u64 << (log_order - 1)]), F::one());
+            }
+        }
+    }
+
+    #[test]
+    fn test_roots_of_unity() {
+        test_root_of_unity::<Fp>();
+        test_root_of_unity::<Fq>();
+    }
+
+    #[test]
+    fn test_batch_invert() {
+        let values = (1..20).map(|i| Fp::from(i * 17 + 5)).collect::<Vec<_>>();
+        let mut inverses = values.clone();
+        batch_invert(&mut inverses);
+        for (v, inv) in values.iter().zip(inverses.iter()) {
+            assert_eq!(*v * inv, Fp::one());
+        }
+    }
+
+    #[test]
+    fn test_from_uniform_bytes() {
+        // Reducing the reversed bytes as a big-endian integer, as the default
+        // implementation does, agrees with pasta's little-endian from_bytes_wide.
+        let bytes = (0..64).map(|i| (i * 37 + 11) as u8).collect::<Vec<u8>>();
+        let mut reversed = bytes.clone();
+        reversed.reverse();
+
+        let expected = Fp::from_uniform_bytes(&bytes);
+        let base = Fp::from(256);
+        let actual = reversed
+            .iter()
+            .fold(Fp::zero(), |acc, b| acc * base + Fp::from(*b as u64));
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,203 +1,407 @@
-use crate::fft::fft;
-use crate::tree::MerkleTree;
+use crate::domain::{FoldingDomain, MonomialDomain, SubgroupDomain};
+use crate::evaluations::Evaluations;
+use crate::field::{ExtensionField, FriField};
+use crate::hasher::{MerkleHasher, Sha3Hasher};
+use crate::parameters::FriParameters;
+use crate::transcript::FiatShamirTranscript;
+use crate::tree::{check_arity, MerkleTree};
 use crate::unipoly::UniPoly;
 use crate::utils::sample_indices;
 use crate::{FriProof, LayerProof};
-use ff::PrimeField;
-use merlin::Transcript;
-use pasta_curves::arithmetic::FieldExt;
+use rand_core::{CryptoRng, RngCore};
+use std::marker::PhantomData;
 
-pub struct FriProver<F: PrimeField> {
-    domain: Vec<F>,
+// Commits to a codeword over F and folds it with challenges drawn from E,
+// over the layers of the folding domain D, in Merkle trees hashed with H.
+pub struct FriProver<F, E = F, D = SubgroupDomain<F>, H = Sha3Hasher>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    D: FoldingDomain<F>,
+    H: MerkleHasher<F>,
+{
+    pub(crate) domain: D,
+    pub(crate) hasher: H,
     // Number of colinearity checks per round
-    num_colinearity_checks: usize,
+    pub(crate) num_colinearity_checks: usize,
+    // Whether queries may repeat, as in the conjectured soundness analyses
+    pub(crate) allow_duplicate_queries: bool,
+    // Whether the proof hides the committed polynomial, see prove_degree_hiding
+    pub(crate) hiding: bool,
+    // Height of the Merkle caps, 0 for single roots
+    pub(crate) cap_height: usize,
+    // Number of children of the Merkle tree nodes
+    pub(crate) arity: usize,
+    _marker: PhantomData<(F, E)>,
 }
 
-impl<F> FriProver<F>
+impl<F, E, H> FriProver<F, E, SubgroupDomain<F>, H>
 where
-    F: FieldExt<Repr = [u8; 32]>,
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    H: MerkleHasher<F>,
 {
     pub fn new(max_degree: usize) -> Self {
+        // Are these params OK?
+        Self::with_params(max_degree, 2, 2)
+    }
+
+    pub fn with_params(
+        max_degree: usize,
+        expansion_factor: usize,
+        num_colinearity_checks: usize,
+    ) -> Self {
         // TODO: Allow arbitrary degree
         assert!(max_degree.is_power_of_two());
+        assert!(expansion_factor.is_power_of_two());
 
-        // Are these params OK?
-        let expansion_factor = 2;
-        let num_colinearity_checks = 2;
+        let domain_order = (max_degree * expansion_factor).next_power_of_two();
+        let domain = SubgroupDomain::new(domain_order, expansion_factor);
 
-        let root_of_unity = F::root_of_unity();
-        let root_of_unity_log_2 = F::S;
+        Self::with_domain(domain, num_colinearity_checks)
+    }
 
-        let domain_order = (max_degree * expansion_factor).next_power_of_two();
+    pub fn prove_evaluations(
+        &self,
+        evals: &Evaluations<F>,
+        transcript: &mut impl FiatShamirTranscript<F>,
+    ) -> FriProof<F, E, H::Digest> {
+        assert!(
+            evals.len() == self.domain.size() && evals.offset == self.domain.offset(),
+            "Evaluations over a different domain"
+        );
+        self.prove_codeword(&evals.evals, transcript)
+    }
+}
+
+impl<F, E, D, H> FriProver<F, E, D, H>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    D: MonomialDomain<F>,
+    H: MerkleHasher<F>,
+{
+    pub fn prove_degree(
+        &self,
+        poly: &UniPoly<F>,
+        transcript: &mut impl FiatShamirTranscript<F>,
+    ) -> FriProof<F, E, H::Digest> {
+        self.prove_coeffs(&poly.coeffs, transcript)
+    }
 
-        // Generator for the subgroup with order _subgroup_order_ in the field
-        let domain_generator = root_of_unity.pow(&[
-            2u32.pow(32 - ((domain_order as f64).log2() as u32)) as u64,
-            0,
-            0,
-            0,
-        ]);
+    // Fills the top num_revealed_evaluations coefficients under the degree bound
+    // with random values, so the values a hiding proof opens are uniform. The
+    // caller commits to the blinded polynomial and keeps it to open it later.
+    pub fn blind(&self, poly: &UniPoly<F>, rng: &mut (impl RngCore + CryptoRng)) -> UniPoly<F> {
+        let degree_bound = self.domain.final_poly_len(0);
+        let num_random = self.num_revealed_evaluations();
+        assert!(
+            poly.coeffs.len() + num_random <= degree_bound,
+            "No room under the degree bound to hide the polynomial"
+        );
 
-        let domain = (0..domain_order)
-            .map(|i| domain_generator.pow(&[i as u64, 0, 0, 0]))
-            .collect();
+        let mut coeffs = vec![F::zero(); degree_bound];
+        coeffs[..poly.coeffs.len()].copy_from_slice(&poly.coeffs);
+        for c in coeffs[(degree_bound - num_random)..].iter_mut() {
+            *c = F::random(&mut *rng);
+        }
+        UniPoly::new(coeffs)
+    }
 
-        // Compute the domain generator from the root of unity
+    // Zero-knowledge variant of prove_degree, which commits to poly as given.
+    // Its codeword and a random mask of the same degree bound are both committed
+    // with salted leaves, and only codeword + beta * mask is folded, so the
+    // folded layers and the final polynomial are independent of poly. The
+    // queries still open num_revealed_evaluations values of poly itself, which
+    // reveal nothing only if poly was blinded first, see blind.
+    pub fn prove_degree_hiding(
+        &self,
+        poly: &UniPoly<F>,
+        transcript: &mut impl FiatShamirTranscript<F>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> FriProof<F, E, H::Digest> {
+        let codeword = self.domain.encode(&poly.coeffs);
+        self.prove_codeword_hiding(&codeword, transcript, rng)
+    }
+}
+
+impl<F, E, D, H> FriProver<F, E, D, H>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    D: FoldingDomain<F>,
+    H: MerkleHasher<F>,
+{
+    pub fn with_domain(domain: D, num_colinearity_checks: usize) -> Self {
         Self {
             domain,
+            hasher: H::default(),
             num_colinearity_checks,
+            allow_duplicate_queries: false,
+            hiding: false,
+            cap_height: 0,
+            arity: 2,
+            _marker: PhantomData,
         }
     }
 
-    fn num_rounds(&self) -> usize {
-        let domain_order = self.domain.len();
-        ((domain_order as f64).log2() as usize) - 3 // this `3` is just random
+    pub fn allow_duplicate_queries(mut self, allow: bool) -> Self {
+        self.allow_duplicate_queries = allow;
+        self
     }
 
-    fn fold(&self, codeword: &[F], domain: &[F], alpha: F) -> Vec<F> {
-        assert!(codeword.len() == domain.len());
-        let two_inv = F::from(2).invert().unwrap();
-        let one = F::from(1);
-
-        let n = domain.len();
+    pub fn hiding(mut self, hiding: bool) -> Self {
+        self.hiding = hiding;
+        self
+    }
 
-        let mut folded_codeword = vec![];
-        for i in 0..(n / 2) {
-            // f*(w^2i) = 1/2 * ((1 + alpha * w^-i) * f(w^i) + (1 - alpha * w^-i) * f(-w^i))
-            // w^(n/2) = -1
-            // -w^i = domain[i + n/2]
+    // The cap must fit in the tree of the last committed layer.
+    pub fn cap_height(mut self, cap_height: usize) -> Self {
+        assert!(
+            cap_height <= min_tree_height(&self.domain),
+            "Cap height exceeds the smallest tree"
+        );
+        self.cap_height = cap_height;
+        self
+    }
 
-            //  let omega_pow_minus_i = self.omega.pow(&[i as u64, 0, 0, 0]).invert().unwrap();
-            let omega_pow_minus_i = domain[n - 1 - i];
+    pub fn arity(mut self, arity: usize) -> Self {
+        check_arity(arity);
+        self.arity = arity;
+        self
+    }
 
-            let f_star_eval = two_inv
-                * ((one + alpha * omega_pow_minus_i) * codeword[i]
-                    + (one - alpha * omega_pow_minus_i) * codeword[i + (n / 2)]);
-            folded_codeword.push(f_star_eval);
+    pub fn parameters(&self) -> FriParameters {
+        FriParameters {
+            hiding: self.hiding,
+            cap_height: self.cap_height,
+            arity: self.arity,
+            ..FriParameters::new::<F, E, D>(
+                &self.domain,
+                self.num_colinearity_checks,
+                self.allow_duplicate_queries,
+            )
         }
+    }
 
-        folded_codeword
+    pub(crate) fn new_tree<T>(&self) -> MerkleTree<T, H>
+    where
+        T: ExtensionField<BaseField = F>,
+    {
+        MerkleTree::with_hasher(self.hasher.clone())
+            .with_arity(self.arity)
+            .with_cap_height(self.cap_height)
     }
 
-    fn commit(
-        &self,
-        codeword: &[F],
-        transcript: &mut Transcript,
-    ) -> (Vec<Vec<F>>, Vec<MerkleTree<F>>) {
-        let mut domain = self.domain.clone();
+    // The codeword on layer `round` is either over the base field (first round) or already over E.
+    pub(crate) fn fold<T>(&self, codeword: &[T], round: usize, alpha: E) -> Vec<E>
+    where
+        T: ExtensionField<BaseField = F>,
+        E: From<T>,
+    {
+        assert!(codeword.len() == self.domain.size() >> round);
+        let half = codeword.len() / 2;
+
+        (0..half)
+            .map(|i| {
+                let a = E::from(codeword[i]);
+                let b = E::from(codeword[i + half]);
+                self.domain.fold(round, i, a, b, alpha)
+            })
+            .collect()
+    }
 
-        let mut codewords = vec![codeword.to_vec()];
+    // Folds the (possibly masked) initial codeword through every round. Returns
+    // the folded codewords and the trees of all of them but the last, which is
+    // sent as a polynomial.
+    fn commit<T>(
+        &self,
+        codeword: &[T],
+        transcript: &mut impl FiatShamirTranscript<F>,
+    ) -> (Vec<Vec<E>>, Vec<MerkleTree<E, H>>)
+    where
+        T: ExtensionField<BaseField = F>,
+        E: From<T>,
+    {
+        let num_rounds = self.domain.num_rounds();
+
+        let mut codewords: Vec<Vec<E>> = vec![];
         let mut trees = vec![];
 
-        for i in 0..self.num_rounds() {
-            let current_codeword = &codewords[i];
-
-            let mut tree = MerkleTree::new();
-            let root = tree.commit(current_codeword);
-
-            transcript.append_message(b"root", &root.to_repr());
-            trees.push(tree);
+        for i in 0..num_rounds {
+            let alpha = transcript.squeeze_field::<E>(b"alpha");
 
-            let mut alpha = [0u8; 64];
-            transcript.challenge_bytes(b"alpha", &mut alpha);
-            let alpha = F::from_bytes_wide(&alpha);
+            let next_codeword = match codewords.last() {
+                Some(current_codeword) => self.fold::<E>(current_codeword, i, alpha),
+                None => self.fold::<T>(codeword, i, alpha),
+            };
 
-            let next_codeword = self.fold(current_codeword, &domain, alpha);
-            let mut domain_unique = vec![];
-            domain.iter().map(|x| x.square()).for_each(|x| {
-                if !domain_unique.contains(&x) {
-                    domain_unique.push(x);
-                }
-            });
-            domain = domain_unique;
+            if i < num_rounds - 1 {
+                let mut tree = self.new_tree();
+                tree.commit(&next_codeword);
+                transcript.absorb_cap(tree.cap());
+                trees.push(tree);
+            }
 
-            codewords.push(next_codeword.to_vec())
+            codewords.push(next_codeword);
         }
 
         (codewords, trees)
     }
 
-    fn query(
+    // The coefficients are in the basis of the domain, see FoldingDomain::encode.
+    pub fn prove_coeffs(
         &self,
-        codewords: &[Vec<F>],
-        trees: &[MerkleTree<F>],
-        indices: &[usize],
-    ) -> Vec<LayerProof<F>> {
-        // A domain: w^i
-        // B domain: w^{n/2 + i}
-        // C domain: w^{2i}
-
-        assert!(indices.len() == self.num_colinearity_checks);
-        let mut indices = indices.to_vec();
-
-        let mut queries = vec![];
+        coeffs: &[F],
+        transcript: &mut impl FiatShamirTranscript<F>,
+    ) -> FriProof<F, E, H::Digest> {
+        let codeword = self.domain.encode(coeffs);
+        self.prove_codeword(&codeword, transcript)
+    }
 
-        for (i, codeword) in codewords.iter().enumerate() {
-            // Skip the last codeword since the verifier's gonna check it directly
-            if i == codewords.len() - 1 {
-                continue;
-            }
+    // The codeword must be over the first layer of the domain, in its order.
+    pub fn prove_codeword(
+        &self,
+        codeword: &[F],
+        transcript: &mut impl FiatShamirTranscript<F>,
+    ) -> FriProof<F, E, H::Digest> {
+        assert!(!self.hiding, "Hiding proofs need randomness");
+        assert!(codeword.len() == self.domain.size());
 
-            // Halve the range of the indices
-            indices = indices
-                .iter()
-                .map(|index| {
-                    if codeword.len() == 1 {
-                        0
-                    } else {
-                        index % (codeword.len() / 2)
-                    }
-                })
-                .collect::<Vec<usize>>();
-
-            let a_indices = indices.clone();
-            let b_indices = indices
-                .iter()
-                .map(|index| (codeword.len() / 2) + index)
-                .collect::<Vec<usize>>();
-            let c_indices = indices.clone();
-
-            let mut openings = vec![];
-            for j in 0..self.num_colinearity_checks {
-                let a_y = codeword[a_indices[j]];
-                let a_y_proof = trees[i].open(a_y);
-                let b_y = codeword[b_indices[j]];
-                let b_y_proof = trees[i].open(b_y);
-
-                let c_y = codeword[c_indices[j]];
-                let c_y_proof = trees[i].open(c_y);
-
-                openings.push((a_y_proof, b_y_proof, c_y_proof));
-            }
+        transcript.absorb_bytes(b"fri_parameters", &self.parameters().to_bytes());
 
-            queries.push(LayerProof { openings })
-        }
+        let mut initial_tree = self.new_tree();
+        initial_tree.commit(codeword);
+        transcript.absorb_cap(initial_tree.cap());
 
-        queries
+        let (codewords, trees) = self.commit::<F>(codeword, transcript);
+        self.open(&initial_tree, None, &codewords, &trees, transcript)
     }
 
-    pub fn prove_degree(&self, poly: &UniPoly<F>, transcript: &mut Transcript) -> FriProof<F> {
-        assert!(poly.degree().is_power_of_two());
-
-        let mut coeffs_expanded: Vec<F> = poly.coeffs.clone();
-        coeffs_expanded.resize(self.domain.len(), F::zero());
+    // Same as FriVerifier::num_revealed_evaluations
+    pub fn num_revealed_evaluations(&self) -> usize {
+        2 * self.num_colinearity_checks
+    }
 
-        let codewords = fft(&coeffs_expanded, &self.domain);
+    // As prove_degree_hiding, for a codeword over the first layer of the domain.
+    pub fn prove_codeword_hiding(
+        &self,
+        codeword: &[F],
+        transcript: &mut impl FiatShamirTranscript<F>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> FriProof<F, E, H::Digest> {
+        assert!(self.hiding, "The prover is not in hiding mode");
+        assert!(codeword.len() == self.domain.size());
+
+        transcript.absorb_bytes(b"fri_parameters", &self.parameters().to_bytes());
+
+        let mut initial_tree = self.new_tree();
+        initial_tree.commit_salted(codeword, rng);
+        transcript.absorb_cap(initial_tree.cap());
+
+        let mask_coeffs = (0..self.domain.final_poly_len(0))
+            .map(|_| F::random(&mut *rng))
+            .collect::<Vec<_>>();
+        let mask = self.domain.encode(&mask_coeffs);
+        let mut mask_tree = self.new_tree();
+        mask_tree.commit_salted(&mask, rng);
+        transcript.absorb_cap(mask_tree.cap());
+
+        let beta = transcript.squeeze_field::<E>(b"mask");
+        let masked = codeword
+            .iter()
+            .zip(mask.iter())
+            .map(|(val, mask)| E::from(*val) + beta * E::from(*mask))
+            .collect::<Vec<E>>();
+
+        let (codewords, trees) = self.commit::<E>(&masked, transcript);
+        self.open(
+            &initial_tree,
+            Some(&mask_tree),
+            &codewords,
+            &trees,
+            transcript,
+        )
+    }
 
-        let (codewords, trees) = self.commit(&codewords, transcript);
+    // Sends the final polynomial, then samples the queries and opens every layer.
+    fn open(
+        &self,
+        initial_tree: &MerkleTree<F, H>,
+        mask_tree: Option<&MerkleTree<F, H>>,
+        codewords: &[Vec<E>],
+        trees: &[MerkleTree<E, H>],
+        transcript: &mut impl FiatShamirTranscript<F>,
+    ) -> FriProof<F, E, H::Digest> {
+        // The last codeword is sent as the coefficients of its polynomial.
+        let num_rounds = self.domain.num_rounds();
+        let final_poly = self
+            .domain
+            .final_poly(codewords.last().unwrap(), num_rounds);
+        for val in &final_poly {
+            transcript.absorb_field(b"final_poly", val);
+        }
 
-        let indices = sample_indices(
+        let domain_length = self.domain.size();
+        let mut indices = sample_indices(
             self.num_colinearity_checks,
-            codewords[0].len(),                   // Length of the initial codeword
-            codewords[codewords.len() - 2].len(), // Length of the reduced codeword
+            domain_length,
+            domain_length >> (num_rounds - 1), // Length of the last committed codeword
+            self.allow_duplicate_queries,
             transcript,
-        );
+        )
+        .unwrap_or_else(|e| panic!("Could not sample query indices: {:?}", e));
 
-        let queries = self.query(&codewords, &trees, &indices);
+        indices = reduce_indices(&indices, domain_length / 2);
+        let initial_layer = open_layer(initial_tree, &indices);
+        let mask_layer = mask_tree.map(|tree| open_layer(tree, &indices));
+
+        let mut queries = vec![];
+        for (i, tree) in trees.iter().enumerate() {
+            indices = reduce_indices(&indices, domain_length >> (i + 2));
+            queries.push(open_layer(tree, &indices));
+        }
 
         FriProof {
-            reduced_codeword: codewords[codewords.len() - 1].clone(),
+            final_poly,
+            initial_layer,
+            mask_layer,
             queries,
         }
     }
 }
+
+// Height of the tree of the last committed layer
+pub(crate) fn min_tree_height<F, D>(domain: &D) -> usize
+where
+    F: FriField,
+    D: FoldingDomain<F>,
+{
+    (domain.size() >> (domain.num_rounds() - 1)).trailing_zeros() as usize
+}
+
+pub(crate) fn reduce_indices(indices: &[usize], half: usize) -> Vec<usize> {
+    indices.iter().map(|index| index % half).collect()
+}
+
+// A layer of size 2 * half is queried at a = index and b = index + half,
+// which fold into point index of the next layer.
+pub(crate) fn open_layer<T, H>(
+    tree: &MerkleTree<T, H>,
+    indices: &[usize],
+) -> LayerProof<T, H::Digest>
+where
+    T: ExtensionField,
+    H: MerkleHasher<T::BaseField>,
+{
+    let half = tree.leaves.len() / 2;
+    let openings = indices
+        .iter()
+        .map(|index| (tree.open(*index), tree.open(*index + half)))
+        .collect();
+
+    LayerProof {
+        cap: tree.cap().to_vec(),
+        openings,
+    }
+}
@@ -1,165 +1,542 @@
-use std::marker::PhantomData;
-
+use crate::domain::{FoldingDomain, SubgroupDomain};
+use crate::field::{ExtensionField, FriField};
+use crate::fri_prover::{min_tree_height, reduce_indices};
+use crate::hasher::{Digest, MerkleHasher, Sha3Hasher};
+use crate::parameters::FriParameters;
+use crate::transcript::FiatShamirTranscript;
+use crate::tree::{check_arity, path_steps};
 use crate::unipoly::UniPoly;
 use crate::utils::sample_indices;
-use crate::FriProof;
-use merlin::Transcript;
-use pasta_curves::arithmetic::FieldExt;
-use pasta_curves::group::ff::PrimeField;
-
-pub struct FriVerifier<F: PrimeField<Repr = [u8; 32]> + FieldExt> {
-    domain: Vec<F>,
-    expansion_factor: usize, // (i.e. expansion factor) (info bits) / (total bits)
-    num_colinearity_checks: usize,
+use crate::{FriProof, LayerProof};
+use std::marker::PhantomData;
+
+pub struct FriVerifier<F, E = F, D = SubgroupDomain<F>, H = Sha3Hasher>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    D: FoldingDomain<F>,
+    H: MerkleHasher<F>,
+{
+    pub(crate) domain: D,
+    pub(crate) hasher: H,
+    pub(crate) num_colinearity_checks: usize,
+    pub(crate) allow_duplicate_queries: bool,
+    pub(crate) hiding: bool,
+    pub(crate) cap_height: usize,
+    pub(crate) arity: usize,
+    _marker: PhantomData<(F, E)>,
 }
 
-impl<F: FieldExt<Repr = [u8; 32]>> FriVerifier<F> {
+impl<F, E, H> FriVerifier<F, E, SubgroupDomain<F>, H>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    H: MerkleHasher<F>,
+{
     pub fn new(max_degree: usize) -> Self {
-        // TODO: Allow arbitrary degree
-        assert!(max_degree.is_power_of_two());
-
         // Are these params OK?
-        let expansion_factor = 2;
-        let num_colinearity_checks = 2;
+        Self::with_params(max_degree, 2, 2)
+    }
 
-        let root_of_unity = F::root_of_unity();
-        let root_of_unity_log_2 = F::S;
+    pub fn with_params(
+        max_degree: usize,
+        expansion_factor: usize,
+        num_colinearity_checks: usize,
+    ) -> Self {
+        // TODO: Allow arbitrary degree
+        assert!(max_degree.is_power_of_two());
+        assert!(expansion_factor.is_power_of_two());
 
+        // (i.e. expansion factor) (info bits) / (total bits)
         let domain_order = (max_degree * expansion_factor).next_power_of_two();
+        let domain = SubgroupDomain::new(domain_order, expansion_factor);
 
-        // Generator for the subgroup with order _subgroup_order_ in the field
-        let domain_generator = root_of_unity.pow(&[
-            2u32.pow(32 - ((domain_order as f64).log2() as u32)) as u64,
-            0,
-            0,
-            0,
-        ]);
-
-        let domain = (0..domain_order)
-            .map(|i| domain_generator.pow(&[i as u64, 0, 0, 0]))
-            .collect();
+        Self::with_domain(domain, num_colinearity_checks)
+    }
+}
 
+impl<F, E, D, H> FriVerifier<F, E, D, H>
+where
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    D: FoldingDomain<F>,
+    H: MerkleHasher<F>,
+{
+    pub fn with_domain(domain: D, num_colinearity_checks: usize) -> Self {
         Self {
             domain,
-            expansion_factor,
+            hasher: H::default(),
             num_colinearity_checks,
+            allow_duplicate_queries: false,
+            hiding: false,
+            cap_height: 0,
+            arity: 2,
+            _marker: PhantomData,
         }
     }
 
-    pub fn verify(&self, proof: FriProof<F>, com: F) {
-        let mut transcript =
-            Transcript::new(b"Fast Reed-Solomon Interactive Oracle Proof of Proximity");
+    pub fn allow_duplicate_queries(mut self, allow: bool) -> Self {
+        self.allow_duplicate_queries = allow;
+        self
+    }
 
-        let final_codeword = proof.reduced_codeword;
+    pub fn hiding(mut self, hiding: bool) -> Self {
+        self.hiding = hiding;
+        self
+    }
 
-        // TODO: Precompute this
-        let mut domain_reduced = self.domain.clone();
-        for _ in 0..proof.queries.len() {
-            let mut domain_unique = vec![];
-            domain_reduced.iter().map(|x| x.square()).for_each(|x| {
-                if !domain_unique.contains(&x) {
-                    domain_unique.push(x);
-                }
-            });
-            domain_reduced = domain_unique;
+    pub fn cap_height(mut self, cap_height: usize) -> Self {
+        assert!(
+            cap_height <= min_tree_height(&self.domain),
+            "Cap height exceeds the smallest tree"
+        );
+        self.cap_height = cap_height;
+        self
+    }
+
+    pub fn arity(mut self, arity: usize) -> Self {
+        check_arity(arity);
+        self.arity = arity;
+        self
+    }
+
+    pub fn parameters(&self) -> FriParameters {
+        FriParameters {
+            hiding: self.hiding,
+            cap_height: self.cap_height,
+            arity: self.arity,
+            ..FriParameters::new::<F, E, D>(
+                &self.domain,
+                self.num_colinearity_checks,
+                self.allow_duplicate_queries,
+            )
         }
+    }
+
+    // The number of values of the committed codeword a proof opens: both points
+    // of every query of the initial layer. Hiding proofs need as many random
+    // coefficients at the top of the degree bound, see FriProver::blind.
+    pub fn num_revealed_evaluations(&self) -> usize {
+        2 * self.num_colinearity_checks
+    }
 
-        let interpolant = UniPoly::interpolate(&domain_reduced, &final_codeword);
+    // The number of layers, openings and Merkle path lengths, and the size of the
+    // final polynomial, all follow from the verifier's own parameters.
+    fn check_shape(&self, proof: &FriProof<F, E, H::Digest>) {
+        let num_rounds = self.domain.num_rounds();
+        let log_size = self.domain.size().trailing_zeros() as usize;
 
-        let degree = if final_codeword.len() == 1 {
-            0
-        } else {
-            final_codeword.len() / self.expansion_factor
-        };
+        assert_eq!(
+            proof.queries.len(),
+            num_rounds - 1,
+            "Invalid number of layers"
+        );
 
-        assert!(interpolant.degree() == degree);
+        check_layer_shape(
+            &proof.initial_layer,
+            log_size,
+            self.cap_height,
+            self.arity,
+            self.num_colinearity_checks,
+        );
+
+        // Hiding proofs open the mask along with the initial layer, and both
+        // with salted leaves.
+        assert_eq!(
+            proof.mask_layer.is_some(),
+            self.hiding,
+            "Mask layer doesn't match the hiding mode"
+        );
+        if let Some(mask_layer) = &proof.mask_layer {
+            check_layer_shape(
+                mask_layer,
+                log_size,
+                self.cap_height,
+                self.arity,
+                self.num_colinearity_checks,
+            );
+            for layer in [&proof.initial_layer, mask_layer] {
+                assert!(
+                    layer
+                        .openings
+                        .iter()
+                        .all(|(a, b)| a.salt.is_some() && b.salt.is_some()),
+                    "Unsalted opening in a hiding proof"
+                );
+            }
+        }
+        for (i, layer) in proof.queries.iter().enumerate() {
+            check_layer_shape(
+                layer,
+                log_size - i - 1,
+                self.cap_height,
+                self.arity,
+                self.num_colinearity_checks,
+            );
+        }
+
+        // The zero polynomial has no degree, and is always low degree.
+        let final_poly_len = self.domain.final_poly_len(num_rounds);
+        let degree = UniPoly::new(proof.final_poly.clone()).degree();
+        assert!(
+            degree.is_none_or(|d| d < final_poly_len),
+            "Final polynomial has too high degree"
+        );
+        assert!(
+            proof.final_poly.len() <= final_poly_len,
+            "Invalid final polynomial length"
+        );
+    }
+
+    // Only proves the degree bound of the committed polynomial; claims about
+    // its evaluations go through BaseFold, which binds them in the transcript.
+    pub fn verify(
+        &self,
+        proof: &FriProof<F, E, H::Digest>,
+        com: &[H::Digest],
+        transcript: &mut impl FiatShamirTranscript<F>,
+    ) {
+        let num_rounds = self.domain.num_rounds();
+        let domain_length = self.domain.size();
+        let final_poly = &proof.final_poly;
+
+        self.check_shape(proof);
+        assert_eq!(
+            proof.initial_layer.cap, com,
+            "Initial cap doesn't match the commitment"
+        );
+
+        // Replay the commit phase to get the folding challenges. Every layer is
+        // checked against its cap, which is absorbed here.
+        transcript.absorb_bytes(b"fri_parameters", &self.parameters().to_bytes());
+        transcript.absorb_cap(com);
+
+        // The initial codeword is folded masked as codeword + beta * mask.
+        let mask = proof.mask_layer.as_ref().map(|mask_layer| {
+            transcript.absorb_cap(&mask_layer.cap);
+            (mask_layer, transcript.squeeze_field::<E>(b"mask"))
+        });
 
-        let domain_length = self.domain.len();
+        let mut alphas = vec![];
+        for i in 0..num_rounds {
+            alphas.push(transcript.squeeze_field::<E>(b"alpha"));
+
+            if i < num_rounds - 1 {
+                transcript.absorb_cap(&proof.queries[i].cap);
+            }
+        }
+
+        for val in final_poly {
+            transcript.absorb_field(b"final_poly", val);
+        }
 
         let mut indices = sample_indices(
             self.num_colinearity_checks,
             domain_length,
-            domain_length / 2usize.pow(proof.queries.len() as u32),
-            &mut transcript,
-        );
+            domain_length >> (num_rounds - 1),
+            self.allow_duplicate_queries,
+            transcript,
+        )
+        .unwrap_or_else(|e| panic!("Could not sample query indices: {:?}", e));
+
+        indices = reduce_indices(&indices, domain_length / 2);
+        let mut folded = match mask {
+            Some((mask_layer, beta)) => {
+                let half = domain_length / 2;
+                check_openings(
+                    &proof.initial_layer,
+                    &indices,
+                    half,
+                    &self.hasher,
+                    self.arity,
+                    self.num_colinearity_checks,
+                );
+                check_openings(
+                    mask_layer,
+                    &indices,
+                    half,
+                    &self.hasher,
+                    self.arity,
+                    self.num_colinearity_checks,
+                );
+
+                let masked = |val: F, mask: F| E::from(val) + beta * E::from(mask);
+                indices
+                    .iter()
+                    .zip(proof.initial_layer.openings.iter())
+                    .zip(mask_layer.openings.iter())
+                    .map(|((index, (a, b)), (mask_a, mask_b))| {
+                        let a = masked(a.leaf, mask_a.leaf);
+                        let b = masked(b.leaf, mask_b.leaf);
+                        self.domain.fold(0, *index, a, b, alphas[0])
+                    })
+                    .collect()
+            }
+            None => verify_layer(
+                &proof.initial_layer,
+                &indices,
+                &self.domain,
+                0,
+                &self.hasher,
+                self.arity,
+                alphas[0],
+                self.num_colinearity_checks,
+            ),
+        };
 
-        let mut hash_count = 0;
         for (i, layer) in proof.queries.iter().enumerate() {
-            assert!(
-                layer.openings.len() == self.num_colinearity_checks,
-                "Invalid number of colinearity checks"
+            let half = domain_length >> (i + 2);
+            let prev_indices = indices;
+            indices = reduce_indices(&prev_indices, half);
+            let next_folded = verify_layer(
+                layer,
+                &indices,
+                &self.domain,
+                i + 1,
+                &self.hasher,
+                self.arity,
+                alphas[i + 1],
+                self.num_colinearity_checks,
             );
+            check_folded(layer, &prev_indices, half, &folded);
+            folded = next_folded;
+        }
 
-            // Halve the range of the indices
-            indices = indices
-                .iter()
-                .map(|index| index % (domain_length / 2 >> (i + 1)))
-                .collect::<Vec<usize>>();
+        // The last folded values are those of the final polynomial.
+        for (index, value) in indices.iter().zip(folded.iter()) {
+            assert_eq!(
+                *value,
+                self.domain.eval_final_poly(final_poly, num_rounds, *index),
+                "Final polynomial check failed"
+            );
+        }
+    }
+}
 
-            let a_indices = indices.clone();
-            let b_indices = indices
+// Cap and openings of a layer with 2^depth leaves, in a tree of the given arity
+pub(crate) fn check_layer_shape<T, Dg>(
+    layer: &LayerProof<T, Dg>,
+    depth: usize,
+    cap_height: usize,
+    arity: usize,
+    num_colinearity_checks: usize,
+) where
+    T: ExtensionField,
+    Dg: Digest,
+{
+    assert_eq!(layer.cap.len(), 1 << cap_height, "Invalid Merkle cap size");
+    assert_eq!(
+        layer.openings.len(),
+        num_colinearity_checks,
+        "Invalid number of colinearity checks"
+    );
+    let group_lens = path_steps(depth, cap_height, arity)
+        .into_iter()
+        .map(|step| (1 << step) - 1)
+        .collect::<Vec<usize>>();
+    for (a, b) in &layer.openings {
+        assert!(
+            [a, b].iter().all(|proof| proof
+                .siblings
                 .iter()
-                .map(|index| (domain_length / 2 >> (i + 1)) + index)
-                .collect::<Vec<usize>>();
-            let c_indices = indices.clone();
-
-            // Colinearity checks
-            for (j, (a, b, c)) in layer.openings.iter().enumerate() {
-                let a_y = a.leaf;
-                let b_y = b.leaf;
-                let c_y = c.leaf;
-
-                let a_x = self.domain[a_indices[j]];
-                let b_x = self.domain[b_indices[j]];
-                let c_x = self.domain[c_indices[j]];
-
-                // Check that (a_x, a_y), (b_x, b_y) and (c_x, c_y) are on a straight line.
-                let coeff = (a_y - b_y) * (a_x - b_x).invert().unwrap();
-                let intercept = a_y - coeff * a_x;
-                assert!(c_y == coeff * c_x + intercept);
-
-                // Check Merkle proofs
-
-                a.verify();
-                b.verify();
-                c.verify();
-                hash_count += a.siblings.len() + b.siblings.len() + c.siblings.len();
-
-                // Check that the root is correct
-                assert_eq!(
-                    a.root, b.root,
-                    "Roots of the two Merkle proofs are not equal"
-                );
+                .map(|group| group.len())
+                .eq(group_lens.iter().copied())),
+            "Invalid Merkle proof length"
+        );
+    }
+}
+
+// Check the openings of folding round `round` at the given (already reduced)
+// indices against the layer cap, and return the folded values, at the same
+// indices in the next layer.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify_layer<F, T, E, D, H>(
+    layer: &LayerProof<T, H::Digest>,
+    indices: &[usize],
+    domain: &D,
+    round: usize,
+    hasher: &H,
+    arity: usize,
+    alpha: E,
+    num_colinearity_checks: usize,
+) -> Vec<E>
+where
+    F: FriField,
+    T: ExtensionField<BaseField = F>,
+    E: ExtensionField<BaseField = F> + From<T>,
+    D: FoldingDomain<F>,
+    H: MerkleHasher<F>,
+{
+    let half = domain.size() >> (round + 1);
+    check_openings(layer, indices, half, hasher, arity, num_colinearity_checks);
+
+    indices
+        .iter()
+        .zip(layer.openings.iter())
+        .map(|(index, (a, b))| domain.fold(round, *index, E::from(a.leaf), E::from(b.leaf), alpha))
+        .collect()
+}
+
+// Check that the openings of a layer of size 2 * half are valid Merkle proofs
+// against the cap of the layer, which the caller has already bound, at
+// a = index and b = index + half.
+pub(crate) fn check_openings<T, H>(
+    layer: &LayerProof<T, H::Digest>,
+    indices: &[usize],
+    half: usize,
+    hasher: &H,
+    arity: usize,
+    num_colinearity_checks: usize,
+) where
+    T: ExtensionField,
+    H: MerkleHasher<T::BaseField>,
+{
+    assert_eq!(
+        layer.openings.len(),
+        num_colinearity_checks,
+        "Invalid number of colinearity checks"
+    );
+
+    let height = (2 * half).trailing_zeros() as usize;
+    for (index, (a, b)) in indices.iter().zip(layer.openings.iter()) {
+        assert!(
+            a.verify(hasher, &layer.cap, height, arity)
+                && b.verify(hasher, &layer.cap, height, arity),
+            "Invalid Merkle proof"
+        );
+        assert!(
+            a.index == *index && b.index == index + half,
+            "Opened at the wrong index"
+        );
+    }
+}
 
-                if i == 0 && j == 0 {
-                    assert_eq!(c.root, com, "c.root != com");
-                } else if j > 0 {
-                    assert_eq!(
-                        layer.openings[j - 1].0.root,
-                        c.root,
-                        "a_prev.root != c.root"
-                    );
-
-                    assert_eq!(
-                        layer.openings[j - 1].1.root,
-                        c.root,
-                        "b_prev.root != c.root"
-                    );
-                }
+// The values folded into point `index` (< 2 * half) of a layer must be the ones
+// opened there, as a if index < half and as b otherwise.
+pub(crate) fn check_folded<E, Dg>(
+    layer: &LayerProof<E, Dg>,
+    indices: &[usize],
+    half: usize,
+    folded: &[E],
+) where
+    E: ExtensionField,
+    Dg: Digest,
+{
+    for ((index, (a, b)), value) in indices.iter().zip(layer.openings.iter()).zip(folded) {
+        let opened = if *index < half { a.leaf } else { b.leaf };
+        assert_eq!(opened, *value, "Colinearity check failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FriProver;
+    use merlin::Transcript;
+    use pasta_curves::Fp;
+
+    fn proof() -> FriProof<Fp> {
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64 + 1)).collect());
+        let prover = FriProver::<Fp>::new(16);
+        prover.prove_degree(&poly, &mut Transcript::new(b"test_shape"))
+    }
+
+    fn verify(proof: &FriProof<Fp>) {
+        let com = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp>::new(16).verify(proof, com, &mut Transcript::new(b"test_shape"));
+    }
+
+    #[test]
+    fn test_sizes_and_rounds() {
+        for max_degree in [8, 32, 128] {
+            for expansion_factor in [2, 4, 8] {
+                let poly = UniPoly::new((0..max_degree).map(|i| Fp::from(i as u64 + 1)).collect());
+                let prover = FriProver::<Fp>::with_params(max_degree, expansion_factor, 4);
+                let proof = prover.prove_degree(&poly, &mut Transcript::new(b"test_sizes"));
+
+                let verifier = FriVerifier::<Fp>::with_params(max_degree, expansion_factor, 4);
+                let com = &proof.initial_layer.cap.clone();
+                verifier.verify(&proof, com, &mut Transcript::new(b"test_sizes"));
             }
         }
     }
 
-    pub fn verify_eval(&self, proof: FriProof<F>, com: F, eval: F) {
-        let comm = proof.reduced_codeword[0];
-        // Compute the code word of the quotient polynomial.
-        // Let the prover provide it.
+    #[test]
+    #[should_panic(expected = "Invalid Merkle proof length")]
+    fn test_swapped_layers() {
+        // The openings of one layer, checked as those of another
+        let poly = UniPoly::new((0..64).map(|i| Fp::from(i as u64 + 1)).collect());
+        let prover = FriProver::<Fp>::with_params(64, 8, 4);
+        let mut proof = prover.prove_degree(&poly, &mut Transcript::new(b"test_sizes"));
+        proof.queries.swap(1, 2);
+
+        let verifier = FriVerifier::<Fp>::with_params(64, 8, 4);
+        let com = &proof.initial_layer.cap.clone();
+        verifier.verify(&proof, com, &mut Transcript::new(b"test_sizes"));
+    }
+
+    #[test]
+    fn test_valid_shape() {
+        verify(&proof());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid number of layers")]
+    fn test_truncated_layers() {
+        let mut proof = proof();
+        proof.queries.pop();
+        verify(&proof);
+    }
 
-        // Given the evaluation point and the claimed evaluation, the verifier can calculate
-        // codeword of the quotient polynomial?
+    #[test]
+    #[should_panic(expected = "Invalid number of layers")]
+    fn test_no_layers() {
+        let mut proof = proof();
+        proof.queries.clear();
+        verify(&proof);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid number of layers")]
+    fn test_padded_layers() {
+        let mut proof = proof();
+        proof.queries.push(proof.queries.last().unwrap().clone());
+        verify(&proof);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid number of colinearity checks")]
+    fn test_truncated_openings() {
+        let mut proof = proof();
+        proof.queries[0].openings.pop();
+        verify(&proof);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid number of colinearity checks")]
+    fn test_padded_openings() {
+        let mut proof = proof();
+        let opening = proof.initial_layer.openings[0].clone();
+        proof.initial_layer.openings.push(opening);
+        verify(&proof);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid Merkle proof length")]
+    fn test_truncated_path() {
+        let mut proof = proof();
+        proof.queries[0].openings[0].1.siblings.pop();
+        verify(&proof);
+    }
 
-        // If not compute the codeword of the quotient polynomial,
-        // given the evaluation of $f(gamma) = y$, we can compute the evaluation of the polynomial
-        // at that point (gamma - z)
+    #[test]
+    #[should_panic(expected = "Invalid final polynomial length")]
+    fn test_padded_final_poly() {
+        let mut proof = proof();
+        let len = FriVerifier::<Fp>::new(16)
+            .domain
+            .final_poly_len(proof.queries.len() + 1);
+        proof.final_poly.resize(len + 1, Fp::zero());
+        verify(&proof);
     }
 }
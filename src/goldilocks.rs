@@ -0,0 +1,256 @@
+use crate::extension::{Extendable, Extension};
+use crate::field::{impl_field_ops, sqrt_tonelli_shanks, FriField};
+use ff::{Field, PrimeField};
+use rand_core::RngCore;
+use subtle::{Choice, CtOption};
+
+// The Goldilocks field, p = 2^64 - 2^32 + 1.
+// Elements are kept in canonical form (< p).
+
+const MODULUS: u64 = 0xffff_ffff_0000_0001;
+// 2^64 - p = 2^32 - 1
+const EPSILON: u64 = 0xffff_ffff;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Goldilocks(u64);
+
+pub type GoldilocksExt2 = Extension<Goldilocks, 2>;
+pub type GoldilocksExt3 = Extension<Goldilocks, 3>;
+
+impl Goldilocks {
+    pub const fn new(value: u64) -> Self {
+        Self(if value >= MODULUS {
+            value - MODULUS
+        } else {
+            value
+        })
+    }
+
+    pub fn to_canonical_u64(&self) -> u64 {
+        self.0
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        let (sum, over) = self.0.overflowing_add(rhs.0);
+        // On overflow, sum + 2^64 = sum + EPSILON (mod p), which can't overflow again
+        // since both inputs are below p.
+        let sum = if over { sum + EPSILON } else { sum };
+        Self::new(sum)
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        let (diff, under) = self.0.overflowing_sub(rhs.0);
+        Self(if under {
+            diff.wrapping_add(MODULUS)
+        } else {
+            diff
+        })
+    }
+
+    fn neg(&self) -> Self {
+        Self::zero().sub(self)
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        Self(reduce128((self.0 as u128) * (rhs.0 as u128)))
+    }
+}
+
+// Reduce x < 2^128 using 2^64 = 2^32 - 1 and 2^96 = -1 (mod p).
+fn reduce128(x: u128) -> u64 {
+    let x_lo = x as u64;
+    let x_hi = (x >> 64) as u64;
+    let x_hi_hi = x_hi >> 32;
+    let x_hi_lo = x_hi & EPSILON;
+
+    let (mut t0, borrow) = x_lo.overflowing_sub(x_hi_hi);
+    if borrow {
+        // Subtracting EPSILON is subtracting 2^64 - p; it can't underflow here.
+        t0 -= EPSILON;
+    }
+
+    let t1 = x_hi_lo * EPSILON;
+    let (sum, over) = t0.overflowing_add(t1);
+    let sum = if over { sum + EPSILON } else { sum };
+
+    Goldilocks::new(sum).0
+}
+
+impl From<u64> for Goldilocks {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl_field_ops!(Goldilocks);
+
+impl Field for Goldilocks {
+    fn random(mut rng: impl RngCore) -> Self {
+        loop {
+            let value = rng.next_u64();
+            if value < MODULUS {
+                return Self(value);
+            }
+        }
+    }
+
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    fn one() -> Self {
+        Self(1)
+    }
+
+    fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        CtOption::new(self.pow_vartime([MODULUS - 2]), !self.is_zero())
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        sqrt_tonelli_shanks(self, &[MODULUS - 1], Self::multiplicative_generator())
+    }
+}
+
+impl PrimeField for Goldilocks {
+    type Repr = [u8; 8];
+
+    fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+        let value = u64::from_le_bytes(repr);
+        CtOption::new(Self(value), Choice::from((value < MODULUS) as u8))
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.0.to_le_bytes()
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.0 & 1) as u8)
+    }
+
+    const NUM_BITS: u32 = 64;
+    const CAPACITY: u32 = 63;
+    const S: u32 = 32;
+
+    fn multiplicative_generator() -> Self {
+        Self(7)
+    }
+
+    fn root_of_unity() -> Self {
+        // g^((p - 1) / 2^32)
+        Self::multiplicative_generator().pow_vartime([(MODULUS - 1) >> 32])
+    }
+}
+
+impl FriField for Goldilocks {}
+
+// X^2 = 7, 7 being a non-square
+impl Extendable<2> for Goldilocks {
+    const CHARACTERISTIC: u64 = MODULUS;
+
+    fn reduction() -> [Self; 2] {
+        [Self(7), Self(0)]
+    }
+}
+
+// X^3 = 7, 7 being a non-cube
+impl Extendable<3> for Goldilocks {
+    const CHARACTERISTIC: u64 = MODULUS;
+
+    fn reduction() -> [Self; 3] {
+        [Self(7), Self(0), Self(0)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::ExtensionField;
+
+    // Deterministic pseudo-random values, including ones near the modulus
+    fn values() -> Vec<u64> {
+        let mut values = vec![0, 1, 2, EPSILON, MODULUS - 1, MODULUS - 2, 1 << 63];
+        let mut x = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..100 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            values.push(x % MODULUS);
+        }
+        values
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let p = MODULUS as u128;
+        for a in values() {
+            for b in values() {
+                let (fa, fb) = (Goldilocks(a), Goldilocks(b));
+                let (a, b) = (a as u128, b as u128);
+                assert_eq!((fa + fb).0 as u128, (a + b) % p);
+                assert_eq!((fa - fb).0 as u128, (a + p - b) % p);
+                assert_eq!((fa * fb).0 as u128, (a * b) % p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invert_and_sqrt() {
+        for a in values().into_iter().map(Goldilocks) {
+            if a.is_zero_vartime() {
+                assert!(bool::from(a.invert().is_none()));
+            } else {
+                assert_eq!(a * a.invert().unwrap(), Goldilocks::one());
+            }
+
+            let sqrt = a.square().sqrt().unwrap();
+            assert_eq!(sqrt.square(), a.square());
+        }
+
+        assert!(bool::from(
+            Goldilocks::multiplicative_generator().sqrt().is_none()
+        ));
+    }
+
+    #[test]
+    fn test_root_of_unity() {
+        let root = Goldilocks::root_of_unity();
+        assert_eq!(root.pow_vartime([1u64 << 32]), Goldilocks::one());
+        assert_ne!(root.pow_vartime([1u64 << 31]), Goldilocks::one());
+    }
+
+    #[test]
+    fn test_extensions_are_fields() {
+        // X^2 - 7 and X^3 - 7 are irreducible: 7 is neither a square nor a cube.
+        let seven = Goldilocks(7);
+        assert_ne!(seven.pow_vartime([(MODULUS - 1) / 2]), Goldilocks::one());
+        assert_ne!(seven.pow_vartime([(MODULUS - 1) / 3]), Goldilocks::one());
+
+        fn check<E: ExtensionField<BaseField = Goldilocks>>() {
+            let values = values();
+            for chunk in values.chunks(E::DEGREE) {
+                if chunk.len() < E::DEGREE {
+                    continue;
+                }
+                let a =
+                    E::from_base_slice(&chunk.iter().map(|v| Goldilocks(*v)).collect::<Vec<_>>());
+                if a.is_zero_vartime() {
+                    continue;
+                }
+                assert_eq!(a * a.invert().unwrap(), E::one());
+                assert_eq!(a.square().sqrt().unwrap().square(), a.square());
+                assert_eq!(E::from_bytes(&a.to_bytes()), Some(a));
+            }
+        }
+
+        check::<GoldilocksExt2>();
+        check::<GoldilocksExt3>();
+    }
+}
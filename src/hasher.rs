@@ -0,0 +1,238 @@
+use crate::field::{ExtensionField, FriField};
+use crate::poseidon::{Poseidon2, PoseidonField};
+use pasta_curves::Fp;
+use sha3::{Keccak256, Sha3_256};
+use std::fmt::Debug;
+
+// A Merkle tree node, in the native encoding of the hash that produced it.
+// The names differ from those of ExtensionField, which field digests also are.
+pub trait Digest: Copy + Debug + PartialEq + Eq {
+    fn encoded_len() -> usize;
+
+    fn encode(&self) -> Vec<u8>;
+
+    // Returns None unless the bytes are a canonical encoding.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+// The output of the byte-oriented hashes
+pub type ByteDigest = [u8; 32];
+
+impl Digest for ByteDigest {
+    fn encoded_len() -> usize {
+        32
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok()
+    }
+}
+
+impl<F: FriField> Digest for F {
+    fn encoded_len() -> usize {
+        F::num_bytes()
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        ExtensionField::from_bytes(bytes)
+    }
+}
+
+// The hash of a Merkle tree over leaves in F or an extension of it. Every hash
+// is prefixed with the kind of its input, so that leaf digests and internal
+// nodes never hash the same input, and a node can't be opened as a leaf.
+pub trait MerkleHasher<F: FriField>: Clone + Default {
+    type Digest: Digest;
+
+    // H(salt || value) for salted leaves
+    fn hash_leaf<T>(&self, value: &T, salt: Option<&[u8]>) -> Self::Digest
+    where
+        T: ExtensionField<BaseField = F>;
+
+    // The parent of a group of children, two for binary trees
+    fn hash_nodes(&self, children: &[Self::Digest]) -> Self::Digest;
+}
+
+const NODE_TAG: u8 = 0;
+const SALTED_LEAF_TAG: u8 = 1;
+const LEAF_TAG: u8 = 2;
+
+fn leaf_tag(salt: Option<&[u8]>) -> u8 {
+    match salt {
+        Some(_) => SALTED_LEAF_TAG,
+        None => LEAF_TAG,
+    }
+}
+
+fn hash_bytes<H: sha3::Digest>(tag: u8, parts: &[&[u8]]) -> ByteDigest {
+    let mut hasher = H::new();
+    hasher.update([tag]);
+    for part in parts {
+        hasher.update(part);
+    }
+
+    hasher.finalize().as_slice().try_into().unwrap()
+}
+
+// Nodes are SHA3-256(tag || children), and leaves SHA3-256(tag || salt || value)
+// over the canonical encoding of the value.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha3Hasher;
+
+impl<F: FriField> MerkleHasher<F> for Sha3Hasher {
+    type Digest = ByteDigest;
+
+    fn hash_leaf<T>(&self, value: &T, salt: Option<&[u8]>) -> ByteDigest
+    where
+        T: ExtensionField<BaseField = F>,
+    {
+        let tag = leaf_tag(salt);
+        hash_bytes::<Sha3_256>(tag, &[salt.unwrap_or_default(), &value.to_bytes()])
+    }
+
+    fn hash_nodes(&self, children: &[ByteDigest]) -> ByteDigest {
+        let children = children.iter().map(|c| c.as_slice()).collect::<Vec<_>>();
+        hash_bytes::<Sha3_256>(NODE_TAG, &children)
+    }
+}
+
+// The same with Keccak-256, as on the EVM.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeccakHasher;
+
+impl<F: FriField> MerkleHasher<F> for KeccakHasher {
+    type Digest = ByteDigest;
+
+    fn hash_leaf<T>(&self, value: &T, salt: Option<&[u8]>) -> ByteDigest
+    where
+        T: ExtensionField<BaseField = F>,
+    {
+        let tag = leaf_tag(salt);
+        hash_bytes::<Keccak256>(tag, &[salt.unwrap_or_default(), &value.to_bytes()])
+    }
+
+    fn hash_nodes(&self, children: &[ByteDigest]) -> ByteDigest {
+        let children = children.iter().map(|c| c.as_slice()).collect::<Vec<_>>();
+        hash_bytes::<Keccak256>(NODE_TAG, &children)
+    }
+}
+
+// Fields whose elements are large enough to be digests on their own: collisions
+// of a single element take about 2^(|F| / 2) work, so 64-bit and 31-bit fields
+// are left out.
+pub trait PoseidonDigestField: PoseidonField {}
+
+impl PoseidonDigestField for Fp {}
+
+// Field-element nodes for circuits over F: a Poseidon2 sponge over the inputs,
+// with the tag and the length in the capacity, squeezed to a single element.
+#[derive(Clone, Default)]
+pub struct PoseidonHasher<F: PoseidonDigestField> {
+    poseidon: Poseidon2<F>,
+}
+
+impl<F: PoseidonDigestField> PoseidonHasher<F> {
+    fn hash_elements(&self, tag: u8, inputs: &[F]) -> F {
+        let mut state = vec![F::zero(); F::WIDTH];
+        state[F::WIDTH - 1] = F::from(((inputs.len() as u64) << 8) | tag as u64);
+        for chunk in inputs.chunks(F::RATE) {
+            for (s, x) in state.iter_mut().zip(chunk) {
+                *s += x;
+            }
+            self.poseidon.permute(&mut state);
+        }
+        if inputs.is_empty() {
+            self.poseidon.permute(&mut state);
+        }
+
+        state[0]
+    }
+}
+
+impl<F: PoseidonDigestField> MerkleHasher<F> for PoseidonHasher<F> {
+    type Digest = F;
+
+    fn hash_leaf<T>(&self, value: &T, salt: Option<&[u8]>) -> F
+    where
+        T: ExtensionField<BaseField = F>,
+    {
+        // Salt bytes are packed into elements that are below the modulus.
+        let mut inputs = salt
+            .unwrap_or_default()
+            .chunks(F::repr_len() - 1)
+            .map(F::from_uniform_bytes)
+            .collect::<Vec<_>>();
+        inputs.extend(value.to_base_vec());
+
+        self.hash_elements(leaf_tag(salt), &inputs)
+    }
+
+    fn hash_nodes(&self, children: &[F]) -> F {
+        self.hash_elements(NODE_TAG, children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Goldilocks, GoldilocksExt2};
+    use sha3::Digest as _;
+
+    fn check_separation<F, H>(hasher: &H)
+    where
+        F: FriField,
+        H: MerkleHasher<F>,
+    {
+        let (x, y) = (F::from(3), F::from(4));
+        let (a, b) = (hasher.hash_leaf(&x, None), hasher.hash_leaf(&y, None));
+        let node = hasher.hash_nodes(&[a, b]);
+
+        assert_ne!(hasher.hash_leaf(&x, None), hasher.hash_leaf(&y, None));
+        assert_ne!(hasher.hash_leaf(&x, None), hasher.hash_leaf(&x, Some(&[])));
+        assert_ne!(
+            hasher.hash_leaf(&x, Some(&[1; 32])),
+            hasher.hash_leaf(&x, Some(&[2; 32]))
+        );
+        assert_ne!(node, hasher.hash_nodes(&[b, a]));
+        assert_ne!(node, hasher.hash_nodes(&[a, b, a, b]));
+        let d = H::Digest::decode(&node.encode()).unwrap();
+        assert_eq!(d, node);
+    }
+
+    #[test]
+    fn test_hashers() {
+        check_separation::<Fp, _>(&Sha3Hasher);
+        check_separation::<Fp, _>(&KeccakHasher);
+        check_separation::<Fp, _>(&PoseidonHasher::default());
+    }
+
+    #[test]
+    fn test_standard_encoding() {
+        // Nodes can be recomputed with any SHA3-256 implementation.
+        let left = [1u8; 32];
+        let right = [2u8; 32];
+        let mut input = vec![NODE_TAG];
+        input.extend_from_slice(&left);
+        input.extend_from_slice(&right);
+        let expected: ByteDigest = Sha3_256::digest(&input).into();
+        assert_eq!(
+            MerkleHasher::<Fp>::hash_nodes(&Sha3Hasher, &[left, right]),
+            expected
+        );
+
+        // Extension leaves are hashed over their base field coefficients.
+        let leaf = GoldilocksExt2::from_base_slice(&[Goldilocks::from(5), Goldilocks::from(6)]);
+        let mut input = vec![LEAF_TAG];
+        input.extend_from_slice(&leaf.to_bytes());
+        let expected: ByteDigest = Keccak256::digest(&input).into();
+        assert_eq!(KeccakHasher.hash_leaf(&leaf, None), expected);
+    }
+}
@@ -1,32 +1,88 @@
+mod babybear;
+mod basefold;
+mod circle;
+mod circle_fri;
+mod domain;
+mod ec_fri;
+mod ecfft;
+mod evaluations;
+mod extension;
 mod fft;
+mod field;
 mod fri_prover;
 mod fri_verifier;
+mod goldilocks;
+mod hasher;
+mod mersenne31;
+mod multilinear;
+mod parameters;
+mod poseidon;
+mod proof_size;
+mod secp256k1;
+mod serialization;
+mod transcript;
 mod tree;
 mod unipoly;
 mod utils;
 
-use pasta_curves::arithmetic::FieldExt;
-use tree::MerkleProof;
-
+pub use babybear::{BabyBear, BabyBearExt4};
+pub use basefold::{BaseFoldProof, BaseFoldProver, BaseFoldVerifier};
+pub use circle::{circle_domain, circle_fft, circle_ifft, CirclePoint};
+pub use circle_fri::{CircleDomain, CircleFriProver, CircleFriVerifier};
+pub use domain::{FoldingDomain, MonomialDomain, SubgroupDomain};
+pub use ec_fri::{EcFftDomain, EcFriProver, EcFriVerifier};
+pub use ecfft::{secp256k1_curve, Curve, CurvePoint, EcFftTree, Isogeny};
+pub use evaluations::Evaluations;
+pub use extension::{Extendable, Extension};
+pub use field::{ExtensionField, FriField};
 pub use fri_prover::FriProver;
 pub use fri_verifier::FriVerifier;
+pub use goldilocks::{Goldilocks, GoldilocksExt2, GoldilocksExt3};
+pub use hasher::{
+    ByteDigest, Digest, KeccakHasher, MerkleHasher, PoseidonDigestField, PoseidonHasher, Sha3Hasher,
+};
 pub use merlin::Transcript;
+pub use mersenne31::{Mersenne31, Mersenne31Ext4};
+pub use multilinear::MultilinearPoly;
+pub use parameters::FriParameters;
+pub use poseidon::{Poseidon2, PoseidonField};
+pub use proof_size::{LayerSize, ProofSize};
+pub use secp256k1::Secp256k1Base;
+pub use serialization::SerializationError;
+pub use transcript::{FiatShamirTranscript, KeccakTranscript, PoseidonTranscript};
+pub use tree::{MerkleProof, MerkleTree, SALT_LEN};
 pub use unipoly::UniPoly;
+pub use utils::SamplingError;
 
-#[derive(Debug)]
-pub struct LayerProof<F>
+// Openings (a, b) of one folding round: the two points of this layer that fold
+// into one point of the next layer, where it is one of the next round's openings.
+// Their paths end at the cap of the layer's tree, which is sent once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerProof<T, D = ByteDigest>
 where
-    F: FieldExt<Repr = [u8; 32]>,
+    T: ExtensionField,
+    D: Digest,
 {
-    pub openings: Vec<(MerkleProof<F>, MerkleProof<F>, MerkleProof<F>)>,
+    pub cap: Vec<D>,
+    pub openings: Vec<(MerkleProof<T, D>, MerkleProof<T, D>)>,
 }
 
-pub struct FriProof<F>
+// The committed codeword is over the base field F. Folding challenges, and so
+// every folded layer, are in E, which is either F itself or an extension of it.
+// The last layer is sent as the coefficients of its polynomial, in the basis of
+// the folding domain. Hiding proofs also open the committed mask, at the same
+// points as the initial layer. Merkle nodes are digests of type D.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FriProof<F, E = F, D = ByteDigest>
 where
-    F: FieldExt<Repr = [u8; 32]>,
+    F: FriField,
+    E: ExtensionField<BaseField = F>,
+    D: Digest,
 {
-    pub reduced_codeword: Vec<F>,
-    pub queries: Vec<LayerProof<F>>,
+    pub final_poly: Vec<E>,
+    pub initial_layer: LayerProof<F, D>,
+    pub mask_layer: Option<LayerProof<F, D>>,
+    pub queries: Vec<LayerProof<E, D>>,
 }
 
 #[cfg(test)]
@@ -34,28 +90,364 @@ mod tests {
     use super::*;
     use crate::unipoly::UniPoly;
     use merlin::Transcript;
-    use pasta_curves::Fp;
+    use pasta_curves::{Fp, Fq};
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
 
-    #[test]
-    fn test_prove() {
+    fn prove_and_verify<F, E>()
+    where
+        F: FriField,
+        E: ExtensionField<BaseField = F>,
+    {
         let poly_degree = 2u32.pow(4u32);
 
         let mut coeffs = vec![];
         for i in 0..(poly_degree + 1) {
-            coeffs.push(Fp::from(i as u64));
+            coeffs.push(F::from(i as u64));
         }
 
         let poly = UniPoly::new(coeffs);
-        let prover = FriProver::<Fp>::new(poly.degree());
+        let prover = FriProver::<F, E>::new(poly.degree().unwrap());
 
         let mut transcript = Transcript::new(b"test_fri");
         let proof = prover.prove_degree(&poly, &mut transcript);
 
-        // C of the first round is the polynomial we're committing to.
-        let poly_commitment = proof.queries[0].openings[0].2.root;
+        // A and B of the first round are openings of the polynomial we're committing to.
+        let poly_commitment = &proof.initial_layer.cap.clone();
+
+        let verifier = FriVerifier::<F, E>::new(poly.degree().unwrap());
+
+        verifier.verify(&proof, poly_commitment, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    fn test_prove() {
+        prove_and_verify::<Fp, Fp>();
+        prove_and_verify::<Fq, Fq>();
+    }
+
+    #[test]
+    fn test_prove_goldilocks() {
+        prove_and_verify::<Goldilocks, Goldilocks>();
+        prove_and_verify::<Goldilocks, GoldilocksExt2>();
+        prove_and_verify::<Goldilocks, GoldilocksExt3>();
+    }
+
+    #[test]
+    fn test_prove_babybear() {
+        prove_and_verify::<BabyBear, BabyBear>();
+        prove_and_verify::<BabyBear, BabyBearExt4>();
+    }
+
+    #[test]
+    fn test_prove_coset() {
+        let coeffs = (0..16).map(|i| Fp::from(i as u64)).collect();
+        let poly = UniPoly::new(coeffs);
+        let offset = Fp::from(7);
+
+        let prover = FriProver::<Fp>::with_domain(SubgroupDomain::coset(offset, 64, 4), 4);
+        let proof = prover.prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+
+        let verifier = FriVerifier::<Fp>::with_domain(SubgroupDomain::coset(offset, 64, 4), 4);
+        let com = &proof.initial_layer.cap.clone();
+        verifier.verify(&proof, com, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    fn test_prove_evaluations() {
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let offset = Fp::from(7);
+        let evals = Evaluations::from_poly(&poly, offset, 64);
+
+        let prover = FriProver::<Fp>::with_domain(SubgroupDomain::coset(offset, 64, 4), 4);
+        let proof = prover.prove_evaluations(&evals, &mut Transcript::new(b"test_fri"));
+        assert_eq!(
+            proof,
+            prover.prove_degree(&poly, &mut Transcript::new(b"test_fri"))
+        );
+
+        let verifier = FriVerifier::<Fp>::with_domain(SubgroupDomain::coset(offset, 64, 4), 4);
+        let com = &proof.initial_layer.cap.clone();
+        verifier.verify(&proof, com, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    fn test_prove_small_domains() {
+        for max_degree in [2, 4, 8] {
+            let poly = UniPoly::new((0..max_degree).map(|i| Fp::from(i as u64 + 1)).collect());
+            let proof = FriProver::<Fp>::new(max_degree)
+                .prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+            assert_eq!(proof.queries.len(), 0);
+
+            let com = &proof.initial_layer.cap.clone();
+            FriVerifier::<Fp>::new(max_degree).verify(
+                &proof,
+                com,
+                &mut Transcript::new(b"test_fri"),
+            );
+        }
+    }
+
+    #[test]
+    fn test_prove_lower_degree() {
+        // A polynomial of lower degree than the bound is accepted too.
+        let poly = UniPoly::new((0..5).map(|i| Fp::from(i as u64 + 1)).collect());
+        let prover = FriProver::<Fp>::new(64);
+        let proof = prover.prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+        assert_eq!(proof.final_poly.len(), 1);
+
+        let com = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp>::new(64).verify(&proof, com, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    fn test_prove_zero() {
+        let prover = FriProver::<Fp>::new(16);
+        let proof = prover.prove_degree(&UniPoly::zero(), &mut Transcript::new(b"test_fri"));
+        assert!(proof.final_poly.is_empty());
+
+        let com = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp>::new(16).verify(&proof, com, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    fn test_duplicate_queries() {
+        // More queries than positions in the last committed layer
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let prover = FriProver::<Fp>::with_params(16, 2, 20).allow_duplicate_queries(true);
+        let proof = prover.prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+
+        let verifier = FriVerifier::<Fp>::with_params(16, 2, 20).allow_duplicate_queries(true);
+        let com = &proof.initial_layer.cap.clone();
+        verifier.verify(&proof, com, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Could not sample query indices")]
+    fn test_too_many_queries() {
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let prover = FriProver::<Fp>::with_params(16, 2, 20);
+        prover.prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    fn test_prove_transcripts() {
+        let poly = UniPoly::new((0..16).map(|i| Goldilocks::from(i as u64)).collect());
+        let prover = FriProver::<Goldilocks, GoldilocksExt2>::new(16);
+        let verifier = FriVerifier::<Goldilocks, GoldilocksExt2>::new(16);
+
+        let proof = prover.prove_degree(&poly, &mut KeccakTranscript::new(b"test_fri"));
+        let com = &proof.initial_layer.cap.clone();
+        verifier.verify(&proof, com, &mut KeccakTranscript::new(b"test_fri"));
+
+        let proof = prover.prove_degree(&poly, &mut PoseidonTranscript::new(b"test_fri"));
+        let com = &proof.initial_layer.cap.clone();
+        verifier.verify(&proof, com, &mut PoseidonTranscript::new(b"test_fri"));
+    }
 
-        let verifier = FriVerifier::<Fp>::new(poly.degree());
+    #[test]
+    fn test_prove_hashers() {
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+
+        let prover = FriProver::<Fp, Fp, SubgroupDomain<Fp>, KeccakHasher>::new(16);
+        let proof = prover.prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+        let com: &[ByteDigest] = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp, Fp, SubgroupDomain<Fp>, KeccakHasher>::new(16).verify(
+            &proof,
+            com,
+            &mut Transcript::new(b"test_fri"),
+        );
+
+        // Field-element nodes, with a transcript over the same field
+        let prover = FriProver::<Fp, Fp, SubgroupDomain<Fp>, PoseidonHasher<Fp>>::new(16);
+        let proof = prover.prove_degree(&poly, &mut PoseidonTranscript::new(b"test_fri"));
+        let com: &[Fp] = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp, Fp, SubgroupDomain<Fp>, PoseidonHasher<Fp>>::new(16).verify(
+            &proof,
+            com,
+            &mut PoseidonTranscript::new(b"test_fri"),
+        );
+    }
+
+    #[test]
+    fn test_prove_caps() {
+        let poly = UniPoly::new((0..64).map(|i| Fp::from(i as u64)).collect());
+        let prover = FriProver::<Fp>::with_params(64, 4, 16);
+        let capped_prover = FriProver::<Fp>::with_params(64, 4, 16).cap_height(3);
+
+        let proof = capped_prover.prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+        assert!(proof.queries.iter().all(|layer| layer.cap.len() == 8));
+        let com = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp>::with_params(64, 4, 16)
+            .cap_height(3)
+            .verify(&proof, com, &mut Transcript::new(b"test_fri"));
+
+        // Many queries share the top of every tree.
+        let uncapped = prover.prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+        assert!(proof.size_in_bytes() < uncapped.size_in_bytes());
+        assert_eq!(proof.size_in_bytes(), proof.to_bytes().len());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid Merkle cap size")]
+    fn test_cap_height_mismatch() {
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let proof = FriProver::<Fp>::new(16)
+            .cap_height(1)
+            .prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+
+        let com = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp>::new(16).verify(&proof, com, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cap height exceeds the smallest tree")]
+    fn test_cap_too_high() {
+        // The last committed layer has 16 points.
+        FriProver::<Fp>::new(16).cap_height(5);
+    }
+
+    #[test]
+    fn test_prove_arity() {
+        type Prover = FriProver<Fp, Fp, SubgroupDomain<Fp>, PoseidonHasher<Fp>>;
+        type Verifier = FriVerifier<Fp, Fp, SubgroupDomain<Fp>, PoseidonHasher<Fp>>;
+        let poly = UniPoly::new((0..64).map(|i| Fp::from(i as u64)).collect());
+
+        for (arity, cap_height) in [(4, 0), (8, 0), (4, 1), (16, 2)] {
+            let proof = Prover::with_params(64, 4, 16)
+                .arity(arity)
+                .cap_height(cap_height)
+                .prove_degree(&poly, &mut PoseidonTranscript::new(b"test_fri"));
+            let com = &proof.initial_layer.cap.clone();
+            Verifier::with_params(64, 4, 16)
+                .arity(arity)
+                .cap_height(cap_height)
+                .verify(&proof, com, &mut PoseidonTranscript::new(b"test_fri"));
+
+            assert_eq!(proof.size_in_bytes(), proof.to_bytes().len());
+            assert_eq!(FriProof::from_bytes(&proof.to_bytes()).unwrap(), proof);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid Merkle proof length")]
+    fn test_arity_mismatch() {
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let proof = FriProver::<Fp>::new(16)
+            .arity(4)
+            .prove_degree(&poly, &mut Transcript::new(b"test_fri"));
+
+        let com = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp>::new(16).verify(&proof, com, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Opened at the wrong index")]
+    fn test_transcript_mismatch() {
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let proof =
+            FriProver::<Fp>::new(16).prove_degree(&poly, &mut KeccakTranscript::new(b"test_fri"));
+
+        let com = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp>::new(16).verify(&proof, com, &mut Transcript::new(b"test_fri"));
+    }
+
+    #[test]
+    fn test_prove_hiding() {
+        let poly = UniPoly::new((0..16).map(|i| Goldilocks::from(i as u64)).collect());
+        let prover = FriProver::<Goldilocks, GoldilocksExt2>::with_params(32, 4, 4).hiding(true);
+        let verifier =
+            FriVerifier::<Goldilocks, GoldilocksExt2>::with_params(32, 4, 4).hiding(true);
+        assert_eq!(verifier.num_revealed_evaluations(), 8);
+
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let proof = prover.prove_degree_hiding(&poly, &mut Transcript::new(b"test_fri"), &mut rng);
+        let mask_layer = proof.mask_layer.as_ref().unwrap();
+        for (a, b) in proof
+            .initial_layer
+            .openings
+            .iter()
+            .chain(&mask_layer.openings)
+        {
+            assert!(a.salt.is_some() && b.salt.is_some());
+        }
+
+        let com = &proof.initial_layer.cap.clone();
+        verifier.verify(&proof, com, &mut Transcript::new(b"test_fri"));
+
+        // Fresh randomness gives different folded layers for the same polynomial.
+        let other = prover.prove_degree_hiding(&poly, &mut Transcript::new(b"test_fri"), &mut rng);
+        assert_ne!(proof.final_poly, other.final_poly);
+    }
+
+    #[test]
+    fn test_hiding_openings() {
+        use ff::Field;
+
+        let poly = UniPoly::new((0..16).map(|i| Goldilocks::from(i as u64)).collect());
+        let prover = FriProver::<Goldilocks, GoldilocksExt2>::with_params(32, 4, 4).hiding(true);
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let blinded = prover.blind(&poly, &mut rng);
+        let proof =
+            prover.prove_degree_hiding(&blinded, &mut Transcript::new(b"test_fri"), &mut rng);
+
+        // The openings are values of the committed polynomial, not of poly.
+        let points = prover.domain.layer(0);
+        let opened = proof
+            .initial_layer
+            .openings
+            .iter()
+            .flat_map(|(a, b)| [a, b])
+            .map(|opening| (opening.index, points[opening.index], opening.leaf))
+            .collect::<Vec<_>>();
+        let num_random = prover.num_revealed_evaluations();
+        assert_eq!(opened.len(), num_random);
+        for (_, x, val) in &opened {
+            assert_eq!(*val, blinded.eval(*x));
+            assert_ne!(*val, poly.eval(*x));
+        }
+
+        // A blinded zero polynomial explains the same openings with other random
+        // coefficients x^shift * r(x), found by interpolating r on the opened points.
+        let shift = prover.domain.final_poly_len(0) - num_random;
+        let mut r = UniPoly::zero();
+        for (_, x, val) in &opened {
+            let mut basis =
+                UniPoly::new(vec![*val * x.pow_vartime([shift as u64]).invert().unwrap()]);
+            for (_, y, _) in &opened {
+                if y != x {
+                    let scale = (*x - y).invert().unwrap();
+                    basis = basis.mul_naive(&UniPoly::new(vec![-*y * scale, scale]));
+                }
+            }
+            r = r + basis;
+        }
+        let mut coeffs = vec![Goldilocks::zero(); shift];
+        coeffs.extend(r.coeffs);
+        let other = prover.domain.encode(&coeffs);
+        for (index, _, val) in &opened {
+            assert_eq!(other[*index], *val);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "No room under the degree bound to hide the polynomial")]
+    fn test_hiding_no_room() {
+        // 16 coefficients and 4 random ones don't fit under the bound of 17.
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let prover = FriProver::<Fp>::new(16).hiding(true);
+        prover.blind(&poly, &mut ChaCha20Rng::seed_from_u64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Mask layer doesn't match the hiding mode")]
+    fn test_hiding_mismatch() {
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let prover = FriProver::<Fp>::new(16).hiding(true);
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let proof = prover.prove_degree_hiding(&poly, &mut Transcript::new(b"test_fri"), &mut rng);
 
-        verifier.verify(proof, poly_commitment);
+        let com = &proof.initial_layer.cap.clone();
+        FriVerifier::<Fp>::new(16).verify(&proof, com, &mut Transcript::new(b"test_fri"));
     }
 }
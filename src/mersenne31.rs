@@ -0,0 +1,223 @@
+use crate::extension::{Extendable, Extension};
+use crate::field::{impl_field_ops, sqrt_tonelli_shanks, FriField};
+use ff::{Field, PrimeField};
+use rand_core::RngCore;
+use subtle::{Choice, CtOption};
+
+// The Mersenne31 field, p = 2^31 - 1. Elements are kept canonical (< p) in a u32,
+// and reduction is a shift and an add since 2^31 = 1 (mod p).
+//
+// p - 1 = 2 * 3^2 * 7 * 11 * 31 * 151 * 331 has no large power of two, so the
+// multiplicative group is of no use for radix-2 FFTs (S = 1). Instead,
+// p + 1 = 2^31 is the order of the circle group x^2 + y^2 = 1, see circle.rs.
+
+const MODULUS: u32 = 0x7fff_ffff;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Mersenne31(u32);
+
+pub type Mersenne31Ext4 = Extension<Mersenne31, 4>;
+
+impl Mersenne31 {
+    pub const fn new(value: u32) -> Self {
+        Self(value % MODULUS)
+    }
+
+    pub fn to_canonical_u32(&self) -> u32 {
+        self.0
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        // Both are below p < 2^31, so the sum doesn't overflow. If sum < p the
+        // subtraction wraps to a larger value and min picks the sum.
+        let sum = self.0 + rhs.0;
+        Self(sum.min(sum.wrapping_sub(MODULUS)))
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        let diff = self.0.wrapping_sub(rhs.0);
+        Self(diff.min(diff.wrapping_add(MODULUS)))
+    }
+
+    fn neg(&self) -> Self {
+        Self::zero().sub(self)
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        Self(reduce64(self.0 as u64 * rhs.0 as u64))
+    }
+}
+
+// Reduce x < 2^62 using 2^31 = 1 (mod p).
+fn reduce64(x: u64) -> u32 {
+    // Both halves are below 2^31, and their sum is below 2p.
+    let sum = (x as u32 & MODULUS) + (x >> 31) as u32;
+    sum.min(sum.wrapping_sub(MODULUS))
+}
+
+impl From<u64> for Mersenne31 {
+    fn from(value: u64) -> Self {
+        Self((value % MODULUS as u64) as u32)
+    }
+}
+
+impl_field_ops!(Mersenne31);
+
+impl Field for Mersenne31 {
+    fn random(mut rng: impl RngCore) -> Self {
+        loop {
+            let value = rng.next_u32() >> 1;
+            if value < MODULUS {
+                return Self(value);
+            }
+        }
+    }
+
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    fn one() -> Self {
+        Self(1)
+    }
+
+    fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        CtOption::new(self.pow_vartime([(MODULUS - 2) as u64]), !self.is_zero())
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        sqrt_tonelli_shanks(
+            self,
+            &[(MODULUS - 1) as u64],
+            Self::multiplicative_generator(),
+        )
+    }
+}
+
+impl PrimeField for Mersenne31 {
+    type Repr = [u8; 4];
+
+    fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+        let value = u32::from_le_bytes(repr);
+        CtOption::new(Self::new(value), Choice::from((value < MODULUS) as u8))
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.0.to_le_bytes()
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.0 & 1) as u8)
+    }
+
+    const NUM_BITS: u32 = 31;
+    const CAPACITY: u32 = 30;
+    const S: u32 = 1;
+
+    fn multiplicative_generator() -> Self {
+        Self(7)
+    }
+
+    fn root_of_unity() -> Self {
+        Self(MODULUS - 1)
+    }
+}
+
+impl FriField for Mersenne31 {}
+
+// X^4 = 4 X^2 - 5, the minimal polynomial of u in the tower
+// F_p[i] / (i^2 + 1), then [u] / (u^2 - 2 - i).
+// (Since p = 3 mod 4, no binomial X^4 - c is irreducible.)
+impl Extendable<4> for Mersenne31 {
+    const CHARACTERISTIC: u64 = MODULUS as u64;
+
+    fn reduction() -> [Self; 4] {
+        [-Self(5), Self(0), Self(4), Self(0)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{prime_power_minus_one, ExtensionField};
+
+    fn values() -> Vec<u32> {
+        let mut values = vec![0, 1, 2, MODULUS - 1, MODULUS - 2, 1 << 30];
+        let mut x = 0x9e37_79b9u32;
+        for _ in 0..100 {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            values.push(x % MODULUS);
+        }
+        values
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let p = MODULUS as u64;
+        for a in values() {
+            for b in values() {
+                let (fa, fb) = (Mersenne31(a), Mersenne31(b));
+                let (a, b) = (a as u64, b as u64);
+                assert_eq!((fa + fb).0 as u64, (a + b) % p);
+                assert_eq!((fa - fb).0 as u64, (a + p - b) % p);
+                assert_eq!((fa * fb).0 as u64, (a * b) % p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invert_and_sqrt() {
+        for a in values().into_iter().map(Mersenne31) {
+            if a.is_zero_vartime() {
+                assert!(bool::from(a.invert().is_none()));
+            } else {
+                assert_eq!(a * a.invert().unwrap(), Mersenne31::one());
+            }
+
+            let sqrt = a.square().sqrt().unwrap();
+            assert_eq!(sqrt.square(), a.square());
+        }
+    }
+
+    #[test]
+    fn test_generator() {
+        let g = Mersenne31::multiplicative_generator();
+        for q in [2, 3, 7, 11, 31, 151, 331] {
+            assert_ne!(
+                g.pow_vartime([((MODULUS - 1) / q) as u64]),
+                Mersenne31::one()
+            );
+        }
+    }
+
+    #[test]
+    fn test_extension_is_field() {
+        // X^(p^4) = X but X^(p^2) != X, so X has a minimal polynomial of degree 4.
+        let x = Mersenne31Ext4::from_base_slice(&[0, 1, 0, 0].map(Mersenne31));
+        let frobenius = |k: usize| x * x.pow_vartime(prime_power_minus_one(MODULUS as u64, k));
+        assert_eq!(frobenius(4), x);
+        assert_ne!(frobenius(2), x);
+
+        let values = values();
+        for chunk in values.chunks_exact(4) {
+            let a = Mersenne31Ext4::from_base_slice(
+                &chunk.iter().map(|v| Mersenne31(*v)).collect::<Vec<_>>(),
+            );
+            if a.is_zero_vartime() {
+                continue;
+            }
+            assert_eq!(a * a.invert().unwrap(), Mersenne31Ext4::one());
+            assert_eq!(a.square().sqrt().unwrap().square(), a.square());
+        }
+    }
+}
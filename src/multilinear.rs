@@ -0,0 +1,130 @@
+use crate::field::{ExtensionField, FriField};
+
+pub struct MultilinearPoly<F>
+where
+    F: FriField,
+{
+    // Monomial basis: coeffs[i] is the coefficient of the product of x_j
+    // over the set bits j of i, so x_0 is the lowest bit.
+    pub coeffs: Vec<F>,
+}
+
+impl<F> MultilinearPoly<F>
+where
+    F: FriField,
+{
+    pub fn new(coeffs: Vec<F>) -> Self {
+        assert!(coeffs.len().is_power_of_two());
+        Self { coeffs }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.coeffs.len().trailing_zeros() as usize
+    }
+
+    // The point may be over an extension of F, as are BaseFold's challenges.
+    pub fn eval<E>(&self, point: &[E]) -> E
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        assert!(point.len() == self.num_vars());
+
+        // Fix the variables one at a time, starting from x_0.
+        let mut coeffs = self.coeffs.iter().map(|c| E::from(*c)).collect::<Vec<E>>();
+        for x in point {
+            coeffs = coeffs
+                .chunks(2)
+                .map(|pair| pair[0] + *x * pair[1])
+                .collect();
+        }
+
+        coeffs[0]
+    }
+
+    // Evaluations over the boolean hypercube, indexed the same way as the coefficients.
+    pub fn evals(&self) -> Vec<F> {
+        let mut evals = self.coeffs.clone();
+        for i in 0..self.num_vars() {
+            let bit = 1 << i;
+            for j in 0..evals.len() {
+                if j & bit != 0 {
+                    let low = evals[j ^ bit];
+                    evals[j] += low;
+                }
+            }
+        }
+
+        evals
+    }
+}
+
+// Evaluations of eq(point, b) over the boolean hypercube.
+pub fn eq_evals<E>(point: &[E]) -> Vec<E>
+where
+    E: ExtensionField,
+{
+    let mut evals = vec![E::one()];
+    for x in point {
+        let mut next = evals
+            .iter()
+            .map(|e| *e * (E::one() - x))
+            .collect::<Vec<E>>();
+        next.extend(evals.iter().map(|e| *e * x));
+        evals = next;
+    }
+
+    evals
+}
+
+pub fn eq_eval<E>(a: &[E], b: &[E]) -> E
+where
+    E: ExtensionField,
+{
+    assert!(a.len() == b.len());
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| *a * b + (E::one() - a) * (E::one() - b))
+        .fold(E::one(), |acc, x| acc * x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasta_curves::Fp;
+
+    #[test]
+    fn test_eval() {
+        let coeffs = (0..8).map(|i| Fp::from(i + 1)).collect::<Vec<Fp>>();
+        let poly = MultilinearPoly::new(coeffs.clone());
+        assert_eq!(poly.num_vars(), 3);
+
+        let point = vec![Fp::from(3), Fp::from(5), Fp::from(7)];
+
+        let mut expected = Fp::zero();
+        for (i, coeff) in coeffs.iter().enumerate() {
+            let mut term = *coeff;
+            for (j, x) in point.iter().enumerate() {
+                if i & (1 << j) != 0 {
+                    term *= x;
+                }
+            }
+            expected += term;
+        }
+        assert_eq!(poly.eval(&point), expected);
+
+        // The hypercube evaluations agree with eval() at boolean points,
+        // and summing them against eq() recovers the evaluation at _point_.
+        let evals = poly.evals();
+        let eq = eq_evals(&point);
+        let mut sum = Fp::zero();
+        for (i, eval) in evals.iter().enumerate() {
+            let b = (0..3)
+                .map(|j| Fp::from(((i >> j) & 1) as u64))
+                .collect::<Vec<Fp>>();
+            assert_eq!(poly.eval(&b), *eval);
+            assert_eq!(eq_eval(&point, &b), eq[i]);
+            sum += *eval * eq[i];
+        }
+        assert_eq!(sum, expected);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::domain::FoldingDomain;
+use crate::field::{ExtensionField, FriField};
+
+// Everything the FRI challenges depend on besides the proof: the fields, the
+// domain and degree bound, and the query parameters. Both sides absorb its
+// canonical encoding before the first commitment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FriParameters {
+    // p - 1 of the base field, little-endian, which identifies it
+    pub field: Vec<u8>,
+    pub extension_degree: usize,
+    // FoldingDomain::description
+    pub domain: Vec<u8>,
+    pub domain_size: usize,
+    pub num_rounds: usize,
+    pub final_poly_len: usize,
+    pub num_colinearity_checks: usize,
+    pub allow_duplicate_queries: bool,
+    pub hiding: bool,
+    // Height of the caps that commit to every Merkle tree
+    pub cap_height: usize,
+    pub arity: usize,
+}
+
+impl FriParameters {
+    pub fn new<F, E, D>(
+        domain: &D,
+        num_colinearity_checks: usize,
+        allow_duplicate_queries: bool,
+    ) -> Self
+    where
+        F: FriField,
+        E: ExtensionField<BaseField = F>,
+        D: FoldingDomain<F>,
+    {
+        let num_rounds = domain.num_rounds();
+        Self::with_rounds::<F, E, D>(
+            domain,
+            num_rounds,
+            domain.final_poly_len(num_rounds),
+            num_colinearity_checks,
+            allow_duplicate_queries,
+        )
+    }
+
+    // For protocols that fold a different number of times than FRI over the
+    // domain would, such as BaseFold.
+    pub fn with_rounds<F, E, D>(
+        domain: &D,
+        num_rounds: usize,
+        final_poly_len: usize,
+        num_colinearity_checks: usize,
+        allow_duplicate_queries: bool,
+    ) -> Self
+    where
+        F: FriField,
+        E: ExtensionField<BaseField = F>,
+        D: FoldingDomain<F>,
+    {
+        Self {
+            field: (-F::one()).to_repr().as_ref().to_vec(),
+            extension_degree: E::DEGREE,
+            domain: domain.description(),
+            domain_size: domain.size(),
+            num_rounds,
+            final_poly_len,
+            num_colinearity_checks,
+            allow_duplicate_queries,
+            hiding: false,
+            cap_height: 0,
+            arity: 2,
+        }
+    }
+
+    // Vectors are prefixed with their length, and numbers are u64 LE.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        for part in [&self.field, &self.domain] {
+            bytes.extend_from_slice(&(part.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(part);
+        }
+        for n in [
+            self.extension_degree,
+            self.domain_size,
+            self.num_rounds,
+            self.final_poly_len,
+            self.num_colinearity_checks,
+            self.allow_duplicate_queries as usize,
+            self.hiding as usize,
+            self.cap_height,
+            self.arity,
+        ] {
+            bytes.extend_from_slice(&(n as u64).to_le_bytes());
+        }
+
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::SubgroupDomain;
+    use crate::transcript::FiatShamirTranscript;
+    use crate::{BaseFoldProver, FriProver, MultilinearPoly, UniPoly};
+    use merlin::Transcript;
+    use pasta_curves::Fp;
+
+    // Records every challenge drawn from the transcript.
+    struct Recorder {
+        transcript: Transcript,
+        challenges: Vec<Vec<u8>>,
+    }
+
+    impl Recorder {
+        fn new() -> Self {
+            Self {
+                transcript: Transcript::new(b"test_parameters"),
+                challenges: vec![],
+            }
+        }
+    }
+
+    impl FiatShamirTranscript<Fp> for Recorder {
+        fn absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]) {
+            FiatShamirTranscript::<Fp>::absorb_bytes(&mut self.transcript, label, bytes);
+        }
+
+        fn squeeze_bytes(&mut self, label: &'static [u8], bytes: &mut [u8]) {
+            FiatShamirTranscript::<Fp>::squeeze_bytes(&mut self.transcript, label, bytes);
+            self.challenges.push(bytes.to_vec());
+        }
+    }
+
+    fn fri_challenges(prover: &FriProver<Fp>, poly: &UniPoly<Fp>) -> Vec<Vec<u8>> {
+        let mut recorder = Recorder::new();
+        prover.prove_degree(poly, &mut recorder);
+        recorder.challenges
+    }
+
+    fn assert_all_differ(a: &[Vec<u8>], b: &[Vec<u8>]) {
+        assert!(!a.is_empty());
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert_ne!(x, y);
+        }
+    }
+
+    #[test]
+    fn test_bound_parameters() {
+        let poly = UniPoly::new((0..8).map(|i| Fp::from(i as u64 + 1)).collect());
+        let other_poly = UniPoly::new((0..8).map(|i| Fp::from(i as u64 + 2)).collect());
+        let prover = |offset: u64, expansion_factor: usize, num_checks: usize| {
+            let domain = SubgroupDomain::coset(Fp::from(offset), 64, expansion_factor);
+            FriProver::<Fp>::with_domain(domain, num_checks)
+        };
+
+        let base = fri_challenges(&prover(7, 4, 4), &poly);
+        let variants = [
+            // The commitment
+            fri_challenges(&prover(7, 4, 4), &other_poly),
+            // The domain
+            fri_challenges(&prover(5, 4, 4), &poly),
+            // The degree bound, with the same committed codeword
+            fri_challenges(&prover(7, 2, 4), &poly),
+            // The queries
+            fri_challenges(&prover(7, 4, 5), &poly),
+            fri_challenges(&prover(7, 4, 4).allow_duplicate_queries(true), &poly),
+        ];
+        for variant in &variants {
+            assert_all_differ(&base, variant);
+        }
+    }
+
+    #[test]
+    fn test_bound_claim() {
+        let poly = MultilinearPoly::new((0..16).map(|i| Fp::from(i as u64 + 1)).collect());
+        let prover = BaseFoldProver::<Fp>::new(4);
+        let challenges = |point: &[Fp]| {
+            let mut recorder = Recorder::new();
+            prover.prove_eval(&poly, point, &mut recorder);
+            recorder.challenges
+        };
+
+        let point = (0..4).map(|i| Fp::from(i as u64 + 3)).collect::<Vec<_>>();
+        let mut other_point = point.clone();
+        other_point[3] += Fp::from(1);
+        assert_all_differ(&challenges(&point), &challenges(&other_point));
+    }
+
+    #[test]
+    fn test_encoding() {
+        let prover = FriProver::<Fp>::with_params(16, 4, 8);
+        let parameters = prover.parameters();
+        assert_eq!(parameters.domain_size, 64);
+        assert_eq!(parameters.num_rounds, 3);
+        assert_eq!(parameters.final_poly_len, 3);
+
+        let other = FriParameters {
+            num_colinearity_checks: 9,
+            ..parameters.clone()
+        };
+        assert_ne!(parameters.to_bytes(), other.to_bytes());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::field::FriField;
+use crate::{BabyBear, Goldilocks};
+use ff::PrimeField;
+use pasta_curves::Fp;
+
+// A field with a published Poseidon2 instance (Grassi, Khovratovich and
+// Schofnegger, 2023): the sponge width and rate, the S-box x^ALPHA, the number
+// of rounds for 128-bit security and the diagonal of the internal matrix minus
+// the identity. Round constants follow from these with the Grain LFSR of the
+// reference implementation.
+pub trait PoseidonField: FriField {
+    const WIDTH: usize;
+    const RATE: usize;
+    const ALPHA: u64;
+    const FULL_ROUNDS: usize;
+    const PARTIAL_ROUNDS: usize;
+    const INTERNAL_DIAGONAL: &'static [u64];
+}
+
+// Pallas with t = 3, as in the reference instance poseidon2_instance_pallas.
+impl PoseidonField for Fp {
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const ALPHA: u64 = 5;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 56;
+    const INTERNAL_DIAGONAL: &'static [u64] = &[1, 1, 2];
+}
+
+// Goldilocks with t = 12, as in poseidon2_instance_goldilocks.
+impl PoseidonField for Goldilocks {
+    const WIDTH: usize = 12;
+    const RATE: usize = 8;
+    const ALPHA: u64 = 7;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 22;
+    const INTERNAL_DIAGONAL: &'static [u64] = &[
+        0xc3b6c08e23ba9300,
+        0xd84b5de94a324fb6,
+        0x0d0c371c5b35b84f,
+        0x7964f570e7188037,
+        0x5daf18bbd996604b,
+        0x6743bc47b9595257,
+        0x5528b9362c59bb70,
+        0xac45e25b7127b68b,
+        0xa2077d7dfbb606b5,
+        0xf3faac6faee378ae,
+        0x0c6388b51545e883,
+        0xd27dbb6944917b60,
+    ];
+}
+
+// BabyBear with t = 24, as in poseidon2_instance_babybear. The capacity of 8
+// elements leaves 124 bits.
+impl PoseidonField for BabyBear {
+    const WIDTH: usize = 24;
+    const RATE: usize = 16;
+    const ALPHA: u64 = 7;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 21;
+    const INTERNAL_DIAGONAL: &'static [u64] = &[
+        0x409133f0, 0x1667a8a1, 0x06a6c7b6, 0x6f53160e, 0x273b11d1, 0x03176c5d, 0x72f9bbf9,
+        0x73ceba91, 0x5cdef81d, 0x01393285, 0x46daee06, 0x065d7ba6, 0x52d72d6f, 0x05dd05e0,
+        0x3bab4b63, 0x6ada3842, 0x2fc5fbec, 0x770d61b0, 0x5715aae9, 0x03ef0e90, 0x75b6c770,
+        0x242adf5f, 0x00d0ca4c, 0x36c0e388,
+    ];
+}
+
+// The Grain LFSR of the Poseidon reference, seeded with the instance parameters.
+struct Grain {
+    bits: [bool; 80],
+    pos: usize,
+}
+
+impl Grain {
+    fn new(field_bits: usize, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        // Prime field, S-box x^alpha, then the sizes, MSB first and padded with ones.
+        let fields = [
+            (1, 2),
+            (0, 4),
+            (field_bits, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+        ];
+        let mut bits = [true; 80];
+        let mut i = 0;
+        for (value, len) in fields {
+            for k in (0..len).rev() {
+                bits[i] = (value >> k) & 1 == 1;
+                i += 1;
+            }
+        }
+
+        let mut grain = Self { bits, pos: 0 };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let b = |k: usize| self.bits[(self.pos + k) % 80];
+        let bit = b(62) ^ b(51) ^ b(38) ^ b(23) ^ b(13) ^ b(0);
+        self.bits[self.pos] = bit;
+        self.pos = (self.pos + 1) % 80;
+        bit
+    }
+
+    // Bits are output in pairs, keeping the second one when the first is set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.step();
+            let bit = self.step();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    // NUM_BITS-bit big-endian samples, rejected until they are below the modulus.
+    fn next_field<F: PrimeField>(&mut self) -> F {
+        let num_bits = F::NUM_BITS as usize;
+        loop {
+            let mut repr = F::Repr::default();
+            for k in (0..num_bits).rev() {
+                if self.next_bit() {
+                    repr.as_mut()[k / 8] |= 1 << (k % 8);
+                }
+            }
+            if let Some(val) = Option::from(F::from_repr(repr)) {
+                return val;
+            }
+        }
+    }
+}
+
+// The Poseidon2 permutation of the reference implementation: an initial
+// external linear layer, then half of the full rounds, the partial rounds with
+// the cheaper internal matrix, and the other half of the full rounds.
+#[derive(Clone)]
+pub struct Poseidon2<F: PoseidonField> {
+    full_round_constants: Vec<Vec<F>>,
+    partial_round_constants: Vec<F>,
+    internal_diagonal: Vec<F>,
+}
+
+impl<F: PoseidonField> Poseidon2<F> {
+    pub fn new() -> Self {
+        assert!(F::WIDTH == 3 || F::WIDTH % 4 == 0);
+        assert_eq!(F::INTERNAL_DIAGONAL.len(), F::WIDTH);
+        assert!(F::RATE < F::WIDTH);
+
+        let mut grain = Grain::new(
+            F::NUM_BITS as usize,
+            F::WIDTH,
+            F::FULL_ROUNDS,
+            F::PARTIAL_ROUNDS,
+        );
+        let mut full_round_constants = vec![];
+        let mut partial_round_constants = vec![];
+        let half_full = F::FULL_ROUNDS / 2;
+        for round in 0..(F::FULL_ROUNDS + F::PARTIAL_ROUNDS) {
+            if round < half_full || round >= half_full + F::PARTIAL_ROUNDS {
+                full_round_constants.push((0..F::WIDTH).map(|_| grain.next_field()).collect());
+            } else {
+                partial_round_constants.push(grain.next_field());
+            }
+        }
+
+        Self {
+            full_round_constants,
+            partial_round_constants,
+            internal_diagonal: F::INTERNAL_DIAGONAL.iter().map(|d| F::from(*d)).collect(),
+        }
+    }
+
+    pub fn permute(&self, state: &mut [F]) {
+        assert_eq!(state.len(), F::WIDTH);
+        let half_full = F::FULL_ROUNDS / 2;
+        let full_round = |state: &mut [F], constants: &[F]| {
+            for (s, c) in state.iter_mut().zip(constants) {
+                *s = (*s + c).pow_vartime([F::ALPHA]);
+            }
+            external_layer(state);
+        };
+
+        external_layer(state);
+        for constants in &self.full_round_constants[..half_full] {
+            full_round(state, constants);
+        }
+        for c in &self.partial_round_constants {
+            state[0] = (state[0] + c).pow_vartime([F::ALPHA]);
+            let sum = state.iter().fold(F::zero(), |acc, s| acc + s);
+            for (s, d) in state.iter_mut().zip(&self.internal_diagonal) {
+                *s = *s * d + sum;
+            }
+        }
+        for constants in &self.full_round_constants[half_full..] {
+            full_round(state, constants);
+        }
+    }
+}
+
+impl<F: PoseidonField> Default for Poseidon2<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// circ(2, 1, 1) for width 3. Otherwise the 4x4 matrix of the paper on each
+// chunk of 4, then the sum of the chunks added to each of them.
+fn external_layer<F: PrimeField>(state: &mut [F]) {
+    if state.len() == 3 {
+        let sum = state[0] + state[1] + state[2];
+        for s in state.iter_mut() {
+            *s += sum;
+        }
+        return;
+    }
+
+    for x in state.chunks_exact_mut(4) {
+        let t0 = x[0] + x[1];
+        let t1 = x[2] + x[3];
+        let t2 = x[1].double() + t1;
+        let t3 = x[3].double() + t0;
+        let t4 = t1.double().double() + t3;
+        let t5 = t0.double().double() + t2;
+        x[0] = t3 + t5;
+        x[1] = t5;
+        x[2] = t2 + t4;
+        x[3] = t4;
+    }
+    let mut sums = [F::zero(); 4];
+    for x in state.chunks_exact(4) {
+        for (sum, x) in sums.iter_mut().zip(x) {
+            *sum += x;
+        }
+    }
+    for (i, s) in state.iter_mut().enumerate() {
+        *s += sums[i % 4];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex<F: PrimeField>(hex: &str) -> F {
+        hex.chars().fold(F::zero(), |acc, c| {
+            acc * F::from(16) + F::from(c.to_digit(16).unwrap() as u64)
+        })
+    }
+
+    // The first and last round constants of the reference instances
+    fn check_constants<F: PoseidonField>(first_full: &str, first_partial: &str, last: &str) {
+        let poseidon = Poseidon2::<F>::new();
+        assert_eq!(poseidon.full_round_constants.len(), F::FULL_ROUNDS);
+        assert_eq!(poseidon.partial_round_constants.len(), F::PARTIAL_ROUNDS);
+        assert_eq!(poseidon.full_round_constants[0][0], from_hex(first_full));
+        assert_eq!(poseidon.partial_round_constants[0], from_hex(first_partial));
+        assert_eq!(
+            poseidon.full_round_constants[F::FULL_ROUNDS - 1][F::WIDTH - 1],
+            from_hex(last)
+        );
+    }
+
+    // The permutation of (0, 1, ..., WIDTH - 1) in the reference tests
+    fn check_permutation<F: PoseidonField>(expected: &[&str]) {
+        let mut state = (0..F::WIDTH as u64).map(F::from).collect::<Vec<_>>();
+        Poseidon2::new().permute(&mut state);
+        let expected = expected.iter().map(|h| from_hex(h)).collect::<Vec<F>>();
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn test_round_constants() {
+        check_constants::<Fp>(
+            "360d7470611e473d353f628f76d110f34e71162f31003b7057538c2596426303",
+            "1cbaf2b371dac6a81d0453416d3e235cb8d9e2d4f314f46f6198785f0cd6b9af",
+            "1aae18833f8e1d3ac0fdf01662f60d22bef00a08c6ed38d23b57e34489b53fad",
+        );
+        check_constants::<Goldilocks>("13dcf33aba214f46", "4adf842aa75d4316", "962deba3e9a2cd94");
+        check_constants::<BabyBear>("0fa20c37", "1da78ec2", "5244e9d4");
+    }
+
+    #[test]
+    fn test_permutation() {
+        check_permutation::<Fp>(&[
+            "1a9b54c7512a914dd778282c44b3513fea7251420b9d95750baae059b2268d7a",
+            "1c48ea0994a7d7984ea338a54dbf0c8681f5af883fe988d59ba3380c9f7901fc",
+            "079ddd0a80a3e9414489b526a2770448964766685f4c4842c838f8a23120b401",
+        ]);
+        check_permutation::<Goldilocks>(&[
+            "01eaef96bdf1c0c1",
+            "1f0d2cc525b2540c",
+            "6282c1dfe1e0358d",
+            "e780d721f698e1e6",
+            "280c0b6f753d833b",
+            "1b942dd5023156ab",
+            "43f0df3fcccb8398",
+            "e8e8190585489025",
+            "56bdbf72f77ada22",
+            "7911c32bf9dcd705",
+            "ec467926508fbe67",
+            "6a50450ddf85a6ed",
+        ]);
+        check_permutation::<BabyBear>(&[
+            "2ed3e23d", "12921fb0", "0e659e79", "61d81dc9", "32bae33b", "62486ae3", "1e681b60",
+            "24b91325", "2a2ef5b9", "50e8593e", "5bc818ec", "10691997", "35a14520", "2ba6a3c5",
+            "279d47ec", "55014e81", "5953a67f", "2f403111", "6b8828ff", "1801301f", "2749207a",
+            "3dc9cf21", "3c985ba2", "57a99864",
+        ]);
+    }
+}
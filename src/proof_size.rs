@@ -0,0 +1,121 @@
+use crate::field::{ExtensionField, FriField};
+use crate::hasher::Digest;
+use crate::serialization::{Encoder, Part};
+use crate::FriProof;
+
+// Sizes are those of the canonical encoding, measured by running it (see
+// serialization.rs).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerSize {
+    // The cap, and the sibling paths, leaf indices and salts of the openings
+    pub merkle_paths: usize,
+    pub leaf_values: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofSize {
+    pub final_poly: usize,
+    pub layers: Vec<LayerSize>,
+    // Length prefixes and the tags of optional values
+    pub length_prefixes: usize,
+}
+
+impl ProofSize {
+    pub fn merkle_paths(&self) -> usize {
+        self.layers.iter().map(|layer| layer.merkle_paths).sum()
+    }
+
+    pub fn leaf_values(&self) -> usize {
+        self.layers.iter().map(|layer| layer.leaf_values).sum()
+    }
+
+    pub fn total(&self) -> usize {
+        self.final_poly + self.merkle_paths() + self.leaf_values() + self.length_prefixes
+    }
+}
+
+// Counts the bytes written instead of keeping them.
+impl Encoder for ProofSize {
+    fn put(&mut self, part: Part, bytes: &[u8]) {
+        let len = bytes.len();
+        match part {
+            Part::FinalPoly => self.final_poly += len,
+            Part::MerklePath => self.layers.last_mut().unwrap().merkle_paths += len,
+            Part::LeafValue => self.layers.last_mut().unwrap().leaf_values += len,
+            Part::Framing => self.length_prefixes += len,
+        }
+    }
+
+    fn begin_layer(&mut self) {
+        self.layers.push(LayerSize {
+            merkle_paths: 0,
+            leaf_values: 0,
+        });
+    }
+}
+
+impl<F: FriField, E: ExtensionField<BaseField = F>, D: Digest> FriProof<F, E, D> {
+    pub fn size_breakdown(&self) -> ProofSize {
+        let mut size = ProofSize {
+            final_poly: 0,
+            layers: vec![],
+            length_prefixes: 0,
+        };
+        self.write(&mut size);
+        size
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.size_breakdown().total()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FriProof, FriProver, Transcript, UniPoly};
+    use pasta_curves::Fp;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_size_matches_encoding() {
+        for log_degree in 4..8 {
+            let coeffs = (0..((1 << log_degree) + 1))
+                .map(|i| Fp::from(i as u64))
+                .collect();
+            let poly = UniPoly::new(coeffs);
+            let prover = FriProver::<Fp>::new(poly.degree().unwrap());
+            let proof = prover.prove_degree(&poly, &mut Transcript::new(b"test_proof_size"));
+
+            let breakdown = proof.size_breakdown();
+            assert_eq!(breakdown.layers.len(), proof.queries.len() + 1);
+            assert_eq!(proof.size_in_bytes(), proof.to_bytes().len());
+
+            // Two 32-byte leaves per opening, in every layer
+            for (size, layer) in breakdown
+                .layers
+                .iter()
+                .zip([&proof.initial_layer].into_iter().chain(&proof.queries))
+            {
+                assert_eq!(size.leaf_values, 64 * layer.openings.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_hiding_size_matches_encoding() {
+        let poly = UniPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let prover = FriProver::<Fp>::new(16).hiding(true);
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let proof =
+            prover.prove_degree_hiding(&poly, &mut Transcript::new(b"test_proof_size"), &mut rng);
+
+        let breakdown = proof.size_breakdown();
+        assert_eq!(breakdown.layers.len(), proof.queries.len() + 2);
+        assert_eq!(proof.size_in_bytes(), proof.to_bytes().len());
+        assert_eq!(
+            FriProof::<Fp>::from_bytes(&proof.to_bytes()).unwrap(),
+            proof
+        );
+    }
+}
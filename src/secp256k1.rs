@@ -0,0 +1,364 @@
+use crate::field::{sqrt_tonelli_shanks, FriField};
+use ff::{Field, PrimeField};
+use rand_core::RngCore;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+// The base field of secp256k1, p = 2^256 - 2^32 - 977.
+// Elements are four little-endian u64 limbs in Montgomery form (x * 2^256 mod p).
+//
+// p - 1 = 2 * 3 * 7 * 13441 * (a 205-bit prime), so there is no two-adic subgroup
+// to run fft::fft on (S = 1). See ecfft.rs for the elliptic curve FFT used instead.
+
+const MODULUS: [u64; 4] = [
+    0xffff_fffe_ffff_fc2f,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+];
+// -p^-1 mod 2^64
+const INV: u64 = mont_inv();
+// 2^512 mod p = (2^32 + 977)^2
+const R2: [u64; 4] = [0x0000_07a2_000e_90a1, 1, 0, 0];
+
+const fn mont_inv() -> u64 {
+    // Newton iteration for p^-1 mod 2^64; each step doubles the correct bits.
+    let mut inv = 1u64;
+    let mut i = 0;
+    while i < 6 {
+        inv = inv.wrapping_mul(2u64.wrapping_sub(MODULUS[0].wrapping_mul(inv)));
+        i += 1;
+    }
+    inv.wrapping_neg()
+}
+
+#[inline]
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let sum = a as u128 + b as u128 + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+#[inline]
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let diff = (a as u128).wrapping_sub(b as u128 + (borrow >> 63) as u128);
+    (diff as u64, (diff >> 64) as u64)
+}
+
+#[inline]
+fn mac(acc: u64, a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let sum = acc as u128 + (a as u128) * (b as u128) + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+// a - p if a >= p, given a < 2p (with the top carry bit in `carry`)
+fn sub_modulus_if_needed(a: [u64; 4], carry: u64) -> [u64; 4] {
+    let mut diff = [0u64; 4];
+    let mut borrow = 0;
+    for i in 0..4 {
+        (diff[i], borrow) = sbb(a[i], MODULUS[i], borrow);
+    }
+    // Keep a if the subtraction underflowed and there was no carry out of a.
+    let (_, borrow) = sbb(carry, 0, borrow);
+    if borrow != 0 {
+        a
+    } else {
+        diff
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Secp256k1Base([u64; 4]);
+
+// The limb loops index several arrays in lockstep.
+#[allow(clippy::needless_range_loop)]
+impl Secp256k1Base {
+    // From canonical little-endian limbs; they must be below p.
+    pub fn from_raw(limbs: [u64; 4]) -> Self {
+        Self(limbs).mul(&Self(R2))
+    }
+
+    pub fn to_raw(&self) -> [u64; 4] {
+        self.mul(&Self([1, 0, 0, 0])).0
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        let mut sum = [0u64; 4];
+        let mut carry = 0;
+        for i in 0..4 {
+            (sum[i], carry) = adc(self.0[i], rhs.0[i], carry);
+        }
+        Self(sub_modulus_if_needed(sum, carry))
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        let mut diff = [0u64; 4];
+        let mut borrow = 0;
+        for i in 0..4 {
+            (diff[i], borrow) = sbb(self.0[i], rhs.0[i], borrow);
+        }
+        if borrow != 0 {
+            // Add p back
+            let mut carry = 0;
+            for i in 0..4 {
+                (diff[i], carry) = adc(diff[i], MODULUS[i], carry);
+            }
+        }
+        Self(diff)
+    }
+
+    fn neg(&self) -> Self {
+        Self::zero().sub(self)
+    }
+
+    // Montgomery multiplication (CIOS)
+    fn mul(&self, rhs: &Self) -> Self {
+        let mut t = [0u64; 6];
+        for i in 0..4 {
+            let mut carry = 0;
+            for j in 0..4 {
+                (t[j], carry) = mac(t[j], self.0[i], rhs.0[j], carry);
+            }
+            (t[4], t[5]) = adc(t[4], carry, 0);
+
+            let m = t[0].wrapping_mul(INV);
+            let (_, mut carry) = mac(t[0], m, MODULUS[0], 0);
+            for j in 1..4 {
+                (t[j - 1], carry) = mac(t[j], m, MODULUS[j], carry);
+            }
+            (t[3], carry) = adc(t[4], carry, 0);
+            t[4] = t[5] + carry;
+            t[5] = 0;
+        }
+
+        Self(sub_modulus_if_needed([t[0], t[1], t[2], t[3]], t[4]))
+    }
+}
+
+impl From<u64> for Secp256k1Base {
+    fn from(value: u64) -> Self {
+        Self::from_raw([value, 0, 0, 0])
+    }
+}
+
+impl ConditionallySelectable for Secp256k1Base {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(std::array::from_fn(|i| {
+            u64::conditional_select(&a.0[i], &b.0[i], choice)
+        }))
+    }
+}
+
+impl ConstantTimeEq for Secp256k1Base {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .fold(Choice::from(1), |acc, (a, b)| acc & a.ct_eq(b))
+    }
+}
+
+macro_rules! impl_binop {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident) => {
+        impl $trait for Secp256k1Base {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                Secp256k1Base::$method(&self, &rhs)
+            }
+        }
+
+        impl<'a> $trait<&'a Secp256k1Base> for Secp256k1Base {
+            type Output = Self;
+
+            fn $method(self, rhs: &'a Self) -> Self {
+                Secp256k1Base::$method(&self, rhs)
+            }
+        }
+
+        impl $assign_trait for Secp256k1Base {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = Secp256k1Base::$method(self, &rhs);
+            }
+        }
+
+        impl<'a> $assign_trait<&'a Secp256k1Base> for Secp256k1Base {
+            fn $assign_method(&mut self, rhs: &'a Self) {
+                *self = Secp256k1Base::$method(self, rhs);
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, AddAssign, add_assign);
+impl_binop!(Sub, sub, SubAssign, sub_assign);
+impl_binop!(Mul, mul, MulAssign, mul_assign);
+
+impl Neg for Secp256k1Base {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Secp256k1Base::neg(&self)
+    }
+}
+
+impl Field for Secp256k1Base {
+    fn random(mut rng: impl RngCore) -> Self {
+        let mut wide = [0u8; 48];
+        rng.fill_bytes(&mut wide);
+        Self::from_uniform_bytes(&wide)
+    }
+
+    fn zero() -> Self {
+        Self([0; 4])
+    }
+
+    fn one() -> Self {
+        Self::from(1)
+    }
+
+    fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        let mut p_minus_two = MODULUS;
+        p_minus_two[0] -= 2;
+        CtOption::new(self.pow_vartime(p_minus_two), !self.is_zero())
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        let mut p_minus_one = MODULUS;
+        p_minus_one[0] -= 1;
+        sqrt_tonelli_shanks(self, &p_minus_one, Self::multiplicative_generator())
+    }
+}
+
+impl PrimeField for Secp256k1Base {
+    type Repr = [u8; 32];
+
+    fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+        let limbs: [u64; 4] = std::array::from_fn(|i| {
+            u64::from_le_bytes(repr[(8 * i)..(8 * i + 8)].try_into().unwrap())
+        });
+
+        // Canonical iff limbs < p
+        let mut borrow = 0;
+        for i in 0..4 {
+            (_, borrow) = sbb(limbs[i], MODULUS[i], borrow);
+        }
+        let is_canonical = Choice::from((borrow >> 63) as u8);
+
+        let value = if bool::from(is_canonical) {
+            Self::from_raw(limbs)
+        } else {
+            Self::zero()
+        };
+        CtOption::new(value, is_canonical)
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        let mut repr = [0u8; 32];
+        for (chunk, limb) in repr.chunks_mut(8).zip(self.to_raw().iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        repr
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.to_raw()[0] & 1) as u8)
+    }
+
+    const NUM_BITS: u32 = 256;
+    const CAPACITY: u32 = 255;
+    const S: u32 = 1;
+
+    fn multiplicative_generator() -> Self {
+        Self::from(3)
+    }
+
+    fn root_of_unity() -> Self {
+        -Self::one()
+    }
+}
+
+impl FriField for Secp256k1Base {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p_minus_one_over(q: u64) -> [u64; 4] {
+        // Long division of p - 1 by q, from the top limb down
+        let mut limbs = MODULUS;
+        limbs[0] -= 1;
+        let mut rem = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let cur = (rem << 64) | *limb as u128;
+            *limb = (cur / q as u128) as u64;
+            rem = cur % q as u128;
+        }
+        assert_eq!(rem, 0);
+        limbs
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Secp256k1Base::from(u64::MAX);
+        let b = Secp256k1Base::from(12345);
+
+        // (2^64 - 1)^2 as u128, split into two limbs
+        let prod = (u64::MAX as u128) * (u64::MAX as u128);
+        assert_eq!((a * a).to_raw(), [prod as u64, (prod >> 64) as u64, 0, 0]);
+        assert_eq!((a + b) - b, a);
+        assert_eq!(b - a + a, b);
+        assert_eq!(
+            -Secp256k1Base::one() + Secp256k1Base::one(),
+            Secp256k1Base::zero()
+        );
+
+        // -1 = p - 1
+        let mut p_minus_one = MODULUS;
+        p_minus_one[0] -= 1;
+        assert_eq!((-Secp256k1Base::one()).to_raw(), p_minus_one);
+        assert_eq!((-Secp256k1Base::one()).square(), Secp256k1Base::one());
+    }
+
+    #[test]
+    fn test_invert_and_sqrt() {
+        let mut x = Secp256k1Base::from(7);
+        for _ in 0..50 {
+            assert_eq!(x * x.invert().unwrap(), Secp256k1Base::one());
+            assert_eq!(x.square().sqrt().unwrap().square(), x.square());
+            x = x.square() + Secp256k1Base::from(3);
+        }
+
+        // -1 is not a square since p = 3 mod 4.
+        assert!(bool::from((-Secp256k1Base::one()).sqrt().is_none()));
+    }
+
+    #[test]
+    fn test_repr() {
+        let x = Secp256k1Base::from(0x1234_5678) * Secp256k1Base::from(u64::MAX);
+        assert_eq!(Secp256k1Base::from_repr(x.to_repr()).unwrap(), x);
+
+        let mut repr = [0u8; 32];
+        for (chunk, limb) in repr.chunks_mut(8).zip(MODULUS.iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        assert!(bool::from(Secp256k1Base::from_repr(repr).is_none()));
+    }
+
+    #[test]
+    fn test_generator() {
+        let g = Secp256k1Base::multiplicative_generator();
+        for q in [2, 3, 7, 13441] {
+            assert_ne!(g.pow_vartime(p_minus_one_over(q)), Secp256k1Base::one());
+        }
+        // The remaining prime factor
+        assert_ne!(g.pow_vartime([2 * 3 * 7 * 13441]), Secp256k1Base::one());
+    }
+}
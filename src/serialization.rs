@@ -0,0 +1,469 @@
+use crate::basefold::BaseFoldProof;
+use crate::field::{ExtensionField, FriField};
+use crate::hasher::Digest;
+use crate::tree::{MerkleProof, SALT_LEN};
+use crate::{FriProof, LayerProof};
+
+// Canonical binary encoding of proofs:
+// - field elements are their reprs (coefficient by coefficient for extension
+//   field elements), and must be canonical (< modulus)
+// - vectors are prefixed with their length as a little-endian u32
+// - Merkle leaf indices are little-endian u64s, and nodes are in the native
+//   encoding of their hash (see Digest)
+// - optional values are prefixed with a byte, 0 if absent and 1 if present
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerializationError {
+    UnexpectedEnd,
+    NonCanonicalFieldElement,
+    TrailingBytes,
+    InvalidOptionTag,
+    InvalidDigest,
+    // A leaf index that doesn't fit in a usize
+    InvalidIndex,
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn read(&mut self, n: usize) -> Result<&'a [u8], SerializationError> {
+        if self.bytes.len() < n {
+            return Err(SerializationError::UnexpectedEnd);
+        }
+
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn read_option<T>(
+        &mut self,
+        read_item: impl Fn(&mut Self) -> Result<T, SerializationError>,
+    ) -> Result<Option<T>, SerializationError> {
+        match self.read(1)?[0] {
+            0 => Ok(None),
+            1 => Ok(Some(read_item(self)?)),
+            _ => Err(SerializationError::InvalidOptionTag),
+        }
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32, SerializationError> {
+        Ok(u32::from_le_bytes(self.read(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64, SerializationError> {
+        Ok(u64::from_le_bytes(self.read(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn read_index(&mut self) -> Result<usize, SerializationError> {
+        usize::try_from(self.read_u64()?).map_err(|_| SerializationError::InvalidIndex)
+    }
+
+    pub(crate) fn read_field<F>(&mut self) -> Result<F, SerializationError>
+    where
+        F: ExtensionField,
+    {
+        F::from_bytes(self.read(F::num_bytes())?)
+            .ok_or(SerializationError::NonCanonicalFieldElement)
+    }
+
+    pub(crate) fn read_digest<D>(&mut self) -> Result<D, SerializationError>
+    where
+        D: Digest,
+    {
+        D::decode(self.read(D::encoded_len())?).ok_or(SerializationError::InvalidDigest)
+    }
+
+    pub(crate) fn read_vec<T>(
+        &mut self,
+        read_item: impl Fn(&mut Self) -> Result<T, SerializationError>,
+    ) -> Result<Vec<T>, SerializationError> {
+        // Don't trust the length prefix for allocation; running out of bytes
+        // is detected item by item.
+        let len = self.read_u32()?;
+        let mut items = vec![];
+        for _ in 0..len {
+            items.push(read_item(self)?);
+        }
+
+        Ok(items)
+    }
+
+    pub(crate) fn finish(&self) -> Result<(), SerializationError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(SerializationError::TrailingBytes)
+        }
+    }
+}
+
+// What each run of encoded bytes is, so that the proof size breakdown is a
+// view over the encoding itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Part {
+    FinalPoly,
+    // Cap nodes, and leaf indices, siblings and salts of the openings
+    MerklePath,
+    LeafValue,
+    // Length prefixes and the tags of optional values
+    Framing,
+}
+
+pub(crate) trait Encoder {
+    fn put(&mut self, part: Part, bytes: &[u8]);
+
+    // Called before the bytes of each LayerProof
+    fn begin_layer(&mut self) {}
+}
+
+impl Encoder for Vec<u8> {
+    fn put(&mut self, _part: Part, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+pub(crate) fn write_len(out: &mut impl Encoder, len: usize) {
+    out.put(Part::Framing, &u32::try_from(len).unwrap().to_le_bytes());
+}
+
+pub(crate) fn write_option<W, T>(out: &mut W, val: &Option<T>, write_item: impl Fn(&mut W, &T))
+where
+    W: Encoder,
+{
+    match val {
+        Some(val) => {
+            out.put(Part::Framing, &[1]);
+            write_item(out, val);
+        }
+        None => out.put(Part::Framing, &[0]),
+    }
+}
+
+pub(crate) fn write_field<F>(out: &mut impl Encoder, part: Part, val: &F)
+where
+    F: ExtensionField,
+{
+    out.put(part, &val.to_bytes());
+}
+
+impl<F: ExtensionField, D: Digest> MerkleProof<F, D> {
+    pub(crate) fn write(&self, out: &mut impl Encoder) {
+        write_field(out, Part::LeafValue, &self.leaf);
+        out.put(Part::MerklePath, &(self.index as u64).to_le_bytes());
+        write_len(out, self.siblings.len());
+        for group in &self.siblings {
+            write_len(out, group.len());
+            for sibling in group {
+                out.put(Part::MerklePath, &sibling.encode());
+            }
+        }
+        write_option(out, &self.salt, |out, salt| out.put(Part::MerklePath, salt));
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, SerializationError> {
+        let leaf = reader.read_field()?;
+        let index = reader.read_index()?;
+        let siblings = reader.read_vec(|r| r.read_vec(|r| r.read_digest()))?;
+        let salt = reader.read_option(|r| Ok(r.read(SALT_LEN)?.try_into().unwrap()))?;
+
+        Ok(Self {
+            leaf,
+            index,
+            siblings,
+            salt,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write(&mut bytes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self::read(&mut reader)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+}
+
+impl<T: ExtensionField, D: Digest> LayerProof<T, D> {
+    pub(crate) fn write(&self, out: &mut impl Encoder) {
+        out.begin_layer();
+        write_len(out, self.cap.len());
+        for node in &self.cap {
+            out.put(Part::MerklePath, &node.encode());
+        }
+
+        write_len(out, self.openings.len());
+        for (a, b) in &self.openings {
+            a.write(out);
+            b.write(out);
+        }
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, SerializationError> {
+        let cap = reader.read_vec(|r| r.read_digest())?;
+        let openings = reader.read_vec(|r| Ok((MerkleProof::read(r)?, MerkleProof::read(r)?)))?;
+
+        Ok(Self { cap, openings })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write(&mut bytes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self::read(&mut reader)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+}
+
+impl<F: FriField, E: ExtensionField<BaseField = F>, D: Digest> FriProof<F, E, D> {
+    pub(crate) fn write(&self, out: &mut impl Encoder) {
+        write_len(out, self.final_poly.len());
+        for val in &self.final_poly {
+            write_field(out, Part::FinalPoly, val);
+        }
+
+        self.initial_layer.write(out);
+        write_option(out, &self.mask_layer, |out, layer| layer.write(out));
+
+        write_len(out, self.queries.len());
+        for layer in &self.queries {
+            layer.write(out);
+        }
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, SerializationError> {
+        let final_poly = reader.read_vec(|r| r.read_field())?;
+        let initial_layer = LayerProof::read(reader)?;
+        let mask_layer = reader.read_option(LayerProof::read)?;
+        let queries = reader.read_vec(LayerProof::read)?;
+
+        Ok(Self {
+            final_poly,
+            initial_layer,
+            mask_layer,
+            queries,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write(&mut bytes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self::read(&mut reader)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+}
+
+// The sumcheck rounds, three evaluations each, then the FRI proof.
+impl<F: FriField, E: ExtensionField<BaseField = F>, D: Digest> BaseFoldProof<F, E, D> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        write_len(&mut bytes, self.sumcheck_evals.len());
+        for val in self.sumcheck_evals.iter().flatten() {
+            bytes.extend_from_slice(&val.to_bytes());
+        }
+        self.fri_proof.write(&mut bytes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let mut reader = Reader::new(bytes);
+        let sumcheck_evals =
+            reader.read_vec(|r| Ok([r.read_field()?, r.read_field()?, r.read_field()?]))?;
+        let fri_proof = FriProof::read(&mut reader)?;
+        reader.finish()?;
+
+        Ok(Self {
+            sumcheck_evals,
+            fri_proof,
+        })
+    }
+}
+
+// serde support goes through the canonical encoding, so the field type
+// doesn't need to implement serde itself.
+#[cfg(feature = "serde")]
+macro_rules! impl_serde {
+    ($type:ident<$($param:ident),+> where $($bounds:tt)+) => {
+        impl<$($param),+> serde::Serialize for $type<$($param),+>
+        where
+            $($bounds)+
+        {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(&self.to_bytes())
+            }
+        }
+
+        impl<'de, $($param),+> serde::Deserialize<'de> for $type<$($param),+>
+        where
+            $($bounds)+
+        {
+            fn deserialize<De: serde::Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+                struct Visitor<$($param),+>(std::marker::PhantomData<($($param,)+)>);
+
+                impl<'de, $($param),+> serde::de::Visitor<'de> for Visitor<$($param),+>
+                where
+                    $($bounds)+
+                {
+                    type Value = $type<$($param),+>;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str(concat!("a canonically encoded ", stringify!($type)))
+                    }
+
+                    fn visit_bytes<Err: serde::de::Error>(
+                        self,
+                        v: &[u8],
+                    ) -> Result<Self::Value, Err> {
+                        $type::from_bytes(v).map_err(|e| Err::custom(format!("{:?}", e)))
+                    }
+
+                    fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> Result<Self::Value, A::Error> {
+                        let mut bytes = vec![];
+                        while let Some(b) = seq.next_element::<u8>()? {
+                            bytes.push(b);
+                        }
+                        self.visit_bytes(&bytes)
+                    }
+                }
+
+                deserializer.deserialize_bytes(Visitor(std::marker::PhantomData))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+impl_serde!(MerkleProof<F, D> where F: ExtensionField, D: Digest);
+#[cfg(feature = "serde")]
+impl_serde!(LayerProof<T, D> where T: ExtensionField, D: Digest);
+#[cfg(feature = "serde")]
+impl_serde!(FriProof<F, E, D> where F: FriField, E: ExtensionField<BaseField = F>, D: Digest);
+#[cfg(feature = "serde")]
+impl_serde!(BaseFoldProof<F, E, D> where F: FriField, E: ExtensionField<BaseField = F>, D: Digest);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FriProver, Transcript, UniPoly};
+    use pasta_curves::Fp;
+
+    fn proof() -> FriProof<Fp> {
+        let coeffs = (0..17).map(|i| Fp::from(i as u64)).collect();
+        let poly = UniPoly::new(coeffs);
+        let prover = FriProver::<Fp>::new(poly.degree().unwrap());
+
+        prover.prove_degree(&poly, &mut Transcript::new(b"test_serialization"))
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let proof = proof();
+
+        let bytes = proof.to_bytes();
+        assert_eq!(FriProof::<Fp>::from_bytes(&bytes).unwrap(), proof);
+
+        let layer = &proof.initial_layer;
+        assert_eq!(
+            LayerProof::<Fp>::from_bytes(&layer.to_bytes()).unwrap(),
+            *layer
+        );
+
+        let opening = &layer.openings[0].0;
+        assert_eq!(
+            MerkleProof::<Fp>::from_bytes(&opening.to_bytes()).unwrap(),
+            *opening
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_extension() {
+        use crate::{Goldilocks, GoldilocksExt2};
+
+        let coeffs = (0..17).map(|i| Goldilocks::from(i as u64)).collect();
+        let poly = UniPoly::new(coeffs);
+        let prover = FriProver::<Goldilocks, GoldilocksExt2>::new(poly.degree().unwrap());
+        let proof = prover.prove_degree(&poly, &mut Transcript::new(b"test_serialization"));
+
+        let bytes = proof.to_bytes();
+        assert_eq!(
+            FriProof::<Goldilocks, GoldilocksExt2>::from_bytes(&bytes).unwrap(),
+            proof
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_basefold() {
+        use crate::{BaseFoldProver, MultilinearPoly};
+
+        let poly = MultilinearPoly::new((0..16).map(|i| Fp::from(i as u64)).collect());
+        let point = (0..4).map(|i| Fp::from(i as u64 + 5)).collect::<Vec<_>>();
+        let prover = BaseFoldProver::<Fp>::new(4);
+        let proof = prover.prove_eval(&poly, &point, &mut Transcript::new(b"test_serialization"));
+
+        let bytes = proof.to_bytes();
+        assert_eq!(BaseFoldProof::<Fp>::from_bytes(&bytes).unwrap(), proof);
+        assert_eq!(
+            BaseFoldProof::<Fp>::from_bytes(&bytes[..(bytes.len() - 1)]),
+            Err(SerializationError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn test_reject_malformed() {
+        let bytes = proof().to_bytes();
+
+        assert_eq!(
+            FriProof::<Fp>::from_bytes(&bytes[..(bytes.len() - 1)]),
+            Err(SerializationError::UnexpectedEnd)
+        );
+
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert_eq!(
+            FriProof::<Fp>::from_bytes(&padded),
+            Err(SerializationError::TrailingBytes)
+        );
+
+        // The first coefficient of the final polynomial, replaced by a value above the modulus
+        let mut non_canonical = bytes;
+        non_canonical[4..36].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            FriProof::<Fp>::from_bytes(&non_canonical),
+            Err(SerializationError::NonCanonicalFieldElement)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let proof = proof();
+
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<FriProof<Fp>>(&json).unwrap(), proof);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::field::{ExtensionField, FriField};
+use crate::hasher::Digest;
+use crate::poseidon::{Poseidon2, PoseidonField};
+use merlin::Transcript;
+
+// The Fiat-Shamir transcript of the FRI prover and verifier, over the base field F
+// of the committed codeword. Besides merlin, there are two sponges that are cheaper
+// to verify elsewhere: Keccak for the EVM and Poseidon for circuits over F.
+pub trait FiatShamirTranscript<F: FriField> {
+    fn absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]);
+
+    fn squeeze_bytes(&mut self, label: &'static [u8], bytes: &mut [u8]);
+
+    fn absorb_field<E>(&mut self, label: &'static [u8], val: &E)
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        self.absorb_bytes(label, &val.to_bytes());
+    }
+
+    // Merkle roots are absorbed in the native encoding of their hash.
+    fn absorb_root<D>(&mut self, root: &D)
+    where
+        D: Digest,
+    {
+        self.absorb_bytes(b"root", &root.encode());
+    }
+
+    // Every node of a Merkle cap, a single root with cap height 0
+    fn absorb_cap<D>(&mut self, cap: &[D])
+    where
+        D: Digest,
+    {
+        for node in cap {
+            self.absorb_root(node);
+        }
+    }
+
+    fn squeeze_field<E>(&mut self, label: &'static [u8]) -> E
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let mut bytes = vec![0u8; E::num_random_bytes()];
+        self.squeeze_bytes(label, &mut bytes);
+
+        E::from_random_bytes(&bytes)
+    }
+
+    // num_bits <= 64 uniform bits
+    fn squeeze_bits(&mut self, label: &'static [u8], num_bits: usize) -> u64 {
+        assert!(num_bits <= 64);
+        let mut bytes = [0u8; 8];
+        self.squeeze_bytes(label, &mut bytes);
+
+        u64::from_le_bytes(bytes) & mask(num_bits)
+    }
+
+    // A uniform index below size: masked for powers of two, and by rejection
+    // sampling otherwise, to avoid the bias of a plain reduction.
+    fn squeeze_index(&mut self, label: &'static [u8], size: usize) -> usize {
+        assert!(size > 0);
+        let num_bits = size.next_power_of_two().trailing_zeros() as usize;
+        loop {
+            let index = self.squeeze_bits(label, num_bits) as usize;
+            if index < size {
+                return index;
+            }
+        }
+    }
+}
+
+fn mask(num_bits: usize) -> u64 {
+    if num_bits == 64 {
+        u64::MAX
+    } else {
+        (1 << num_bits) - 1
+    }
+}
+
+// Merlin, i.e. STROBE over Keccak-f[1600]
+impl<F: FriField> FiatShamirTranscript<F> for Transcript {
+    fn absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.append_message(label, bytes);
+    }
+
+    fn squeeze_bytes(&mut self, label: &'static [u8], bytes: &mut [u8]) {
+        self.challenge_bytes(label, bytes);
+    }
+}
+
+const KECCAK_RATE: usize = 136;
+
+// A duplex sponge over Keccak-f[1600] with the rate of Keccak-256. Every message
+// is framed as (label length, label, data length, data), lengths as u64 LE.
+#[derive(Clone)]
+pub struct KeccakTranscript {
+    state: [u64; 25],
+    pos: usize,
+    squeezing: bool,
+}
+
+impl KeccakTranscript {
+    pub fn new(label: &'static [u8]) -> Self {
+        let mut transcript = Self {
+            state: [0; 25],
+            pos: 0,
+            squeezing: false,
+        };
+        transcript.absorb(b"dom-sep", label);
+        transcript
+    }
+
+    fn xor_byte(&mut self, i: usize, b: u8) {
+        self.state[i / 8] ^= (b as u64) << (8 * (i % 8));
+    }
+
+    fn byte(&self, i: usize) -> u8 {
+        (self.state[i / 8] >> (8 * (i % 8))) as u8
+    }
+
+    fn permute(&mut self) {
+        keccak::f1600(&mut self.state);
+        self.pos = 0;
+    }
+
+    fn absorb_raw(&mut self, bytes: &[u8]) {
+        if self.squeezing {
+            self.squeezing = false;
+            self.permute();
+        }
+        for b in bytes {
+            self.xor_byte(self.pos, *b);
+            self.pos += 1;
+            if self.pos == KECCAK_RATE {
+                self.permute();
+            }
+        }
+    }
+
+    fn absorb(&mut self, label: &[u8], bytes: &[u8]) {
+        self.absorb_raw(&(label.len() as u64).to_le_bytes());
+        self.absorb_raw(label);
+        self.absorb_raw(&(bytes.len() as u64).to_le_bytes());
+        self.absorb_raw(bytes);
+    }
+}
+
+impl<F: FriField> FiatShamirTranscript<F> for KeccakTranscript {
+    fn absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.absorb(label, bytes);
+    }
+
+    fn squeeze_bytes(&mut self, label: &'static [u8], bytes: &mut [u8]) {
+        self.absorb(label, &(bytes.len() as u64).to_le_bytes());
+
+        // Keccak padding before switching to squeezing
+        self.xor_byte(self.pos, 0x01);
+        self.xor_byte(KECCAK_RATE - 1, 0x80);
+        self.permute();
+        self.squeezing = true;
+
+        for b in bytes.iter_mut() {
+            if self.pos == KECCAK_RATE {
+                self.permute();
+            }
+            *b = self.byte(self.pos);
+            self.pos += 1;
+        }
+    }
+}
+
+// A duplex sponge over the Poseidon2 permutation of F, absorbing field
+// elements as they are. Bytes are packed into field elements, repr_len - 1 at
+// a time. Labels aren't absorbed: the order of messages is fixed by the protocol, and
+// an in-circuit verifier shouldn't pay for them.
+#[derive(Clone)]
+pub struct PoseidonTranscript<F: PoseidonField> {
+    poseidon: Poseidon2<F>,
+    state: Vec<F>,
+    pos: usize,
+    squeezing: bool,
+}
+
+impl<F: PoseidonField> PoseidonTranscript<F> {
+    pub fn new(label: &'static [u8]) -> Self {
+        let mut transcript = Self {
+            poseidon: Poseidon2::new(),
+            state: vec![F::zero(); F::WIDTH],
+            pos: 0,
+            squeezing: false,
+        };
+        FiatShamirTranscript::<F>::absorb_bytes(&mut transcript, b"dom-sep", label);
+        transcript
+    }
+
+    fn permute(&mut self) {
+        self.poseidon.permute(&mut self.state);
+        self.pos = 0;
+    }
+
+    fn absorb_element(&mut self, val: F) {
+        if self.squeezing {
+            self.squeezing = false;
+            self.permute();
+        }
+        self.state[self.pos] += val;
+        self.pos += 1;
+        if self.pos == F::RATE {
+            self.permute();
+        }
+    }
+
+    fn squeeze_element(&mut self) -> F {
+        if !self.squeezing {
+            // Pad after the last absorbed element, since absorbing zero is a no-op.
+            self.state[self.pos] += F::one();
+            self.squeezing = true;
+            self.permute();
+        } else if self.pos == F::RATE {
+            self.permute();
+        }
+        let val = self.state[self.pos];
+        self.pos += 1;
+        val
+    }
+}
+
+impl<F: PoseidonField> FiatShamirTranscript<F> for PoseidonTranscript<F> {
+    fn absorb_bytes(&mut self, _label: &'static [u8], bytes: &[u8]) {
+        self.absorb_element(F::from(bytes.len() as u64));
+        for chunk in bytes.chunks(F::repr_len() - 1) {
+            self.absorb_element(F::from_uniform_bytes(chunk));
+        }
+    }
+
+    fn squeeze_bytes(&mut self, label: &'static [u8], bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(8) {
+            let val = FiatShamirTranscript::<F>::squeeze_bits(self, label, 8 * chunk.len());
+            chunk.copy_from_slice(&val.to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    fn absorb_field<E>(&mut self, _label: &'static [u8], val: &E)
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        for coeff in val.to_base_vec() {
+            self.absorb_element(coeff);
+        }
+    }
+
+    fn squeeze_field<E>(&mut self, _label: &'static [u8]) -> E
+    where
+        E: ExtensionField<BaseField = F>,
+    {
+        let coeffs = (0..E::DEGREE)
+            .map(|_| self.squeeze_element())
+            .collect::<Vec<_>>();
+        E::from_base_slice(&coeffs)
+    }
+
+    // Elements below 2^(NUM_BITS - 1), at least half of them, give NUM_BITS - 1
+    // uniform bits each; the others are rejected.
+    fn squeeze_bits(&mut self, _label: &'static [u8], num_bits: usize) -> u64 {
+        assert!(num_bits <= 64);
+        let bits_per_element = F::NUM_BITS as usize - 1;
+
+        let mut bits = 0u64;
+        let mut num_squeezed = 0;
+        while num_squeezed < num_bits {
+            let repr = self.squeeze_element().to_repr();
+            let repr = repr.as_ref();
+            if (repr[bits_per_element / 8] >> (bits_per_element % 8)) & 1 == 1
+                || repr[(bits_per_element / 8 + 1)..].iter().any(|b| *b != 0)
+            {
+                continue;
+            }
+
+            let mut low = [0u8; 8];
+            let len = repr.len().min(8);
+            low[..len].copy_from_slice(&repr[..len]);
+
+            let take = bits_per_element.min(num_bits - num_squeezed);
+            bits |= (u64::from_le_bytes(low) & mask(take)) << num_squeezed;
+            num_squeezed += take;
+        }
+
+        bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BabyBear, Goldilocks, GoldilocksExt2};
+    use ff::Field;
+    use pasta_curves::Fp;
+
+    fn check_transcript<F, T>(new: impl Fn() -> T)
+    where
+        F: FriField,
+        T: FiatShamirTranscript<F>,
+    {
+        let challenge = |messages: &[&[u8]]| {
+            let mut transcript = new();
+            for message in messages {
+                transcript.absorb_bytes(b"message", message);
+            }
+            transcript.squeeze_field::<F>(b"challenge")
+        };
+
+        // Deterministic, and bound to every message and to their framing
+        assert_eq!(challenge(&[b"abc"]), challenge(&[b"abc"]));
+        assert_ne!(challenge(&[b"abc"]), challenge(&[b"abd"]));
+        assert_ne!(challenge(&[b"ab", b"c"]), challenge(&[b"a", b"bc"]));
+        assert_ne!(challenge(&[]), challenge(&[b""]));
+
+        // Successive challenges differ.
+        let mut transcript = new();
+        let a = transcript.squeeze_field::<F>(b"challenge");
+        let b = transcript.squeeze_field::<F>(b"challenge");
+        assert_ne!(a, b);
+
+        for size in [1, 6, 16, 1000] {
+            for _ in 0..20 {
+                assert!(transcript.squeeze_index(b"index", size) < size);
+            }
+        }
+        assert!(transcript.squeeze_bits(b"bits", 10) < 1 << 10);
+        transcript.squeeze_bits(b"bits", 64);
+    }
+
+    #[test]
+    fn test_merlin() {
+        check_transcript::<Fp, _>(|| Transcript::new(b"test"));
+    }
+
+    #[test]
+    fn test_keccak() {
+        check_transcript::<Fp, _>(|| KeccakTranscript::new(b"test"));
+        check_transcript::<Goldilocks, _>(|| KeccakTranscript::new(b"test"));
+    }
+
+    #[test]
+    fn test_poseidon() {
+        check_transcript::<Fp, _>(|| PoseidonTranscript::new(b"test"));
+        check_transcript::<Goldilocks, _>(|| PoseidonTranscript::new(b"test"));
+        check_transcript::<BabyBear, _>(|| PoseidonTranscript::new(b"test"));
+
+        // Extension field challenges are squeezed coefficient by coefficient.
+        let mut transcript = PoseidonTranscript::<Goldilocks>::new(b"test");
+        let a = transcript.squeeze_field::<GoldilocksExt2>(b"challenge");
+        assert!(a.to_base_vec().iter().all(|c| *c != Goldilocks::zero()));
+    }
+}
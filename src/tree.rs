@@ -1,77 +1,193 @@
-use crate::utils::hash_two;
-use ff::Field;
-use pasta_curves::arithmetic::FieldExt;
+use crate::field::ExtensionField;
+use crate::hasher::{ByteDigest, Digest, MerkleHasher, Sha3Hasher};
+use rand_core::{CryptoRng, RngCore};
 
-pub struct MerkleTree<F: FieldExt<Repr = [u8; 32]>> {
-    pub layers: Vec<Vec<F>>, // to root
+pub const SALT_LEN: usize = 32;
+
+// Leaves are field elements, and nodes digests of the hasher H over groups of
+// `arity` children. The tree is committed to by its cap, the 2^cap_height
+// nodes at cap_height below the root, and paths stop there. A cap height of 0
+// is the usual single root.
+pub struct MerkleTree<F: ExtensionField, H: MerkleHasher<F::BaseField> = Sha3Hasher> {
+    pub hasher: H,
+    pub arity: usize,
+    pub cap_height: usize,
+    pub leaves: Vec<F>,
+    // One per leaf if the tree is salted, empty otherwise
+    pub salts: Vec<[u8; SALT_LEN]>,
+    // From the leaf digests to the cap, and then to the root
+    pub layers: Vec<Vec<H::Digest>>,
 }
 
-#[derive(Debug)]
-pub struct MerkleProof<F: FieldExt<Repr = [u8; 32]>> {
-    pub root: F,
+// The siblings of each node on the path, in the group of children of its parent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof<F: ExtensionField, D: Digest = ByteDigest> {
     pub leaf: F,
-    pub siblings: Vec<F>,
+    pub index: usize,
+    pub siblings: Vec<Vec<D>>,
+    pub salt: Option<[u8; SALT_LEN]>,
 }
 
-impl<F: FieldExt<Repr = [u8; 32]>> MerkleProof<F> {
-    pub fn verify(&self) -> bool {
-        let mut current_hash = self.leaf;
-        for sibling in &self.siblings {
-            current_hash = hash_two(&[current_hash, *sibling]);
+// The number of binary levels that each step of a path merges, from 2^height
+// nodes up to 2^cap_height: log2(arity) at a time, and whatever is left in the
+// last step, so that any power of two number of leaves can be committed to.
+pub(crate) fn path_steps(height: usize, cap_height: usize, arity: usize) -> Vec<usize> {
+    check_arity(arity);
+    let log_arity = arity.trailing_zeros() as usize;
+
+    let mut steps = vec![];
+    let mut remaining = height - cap_height;
+    while remaining > 0 {
+        let step = remaining.min(log_arity);
+        steps.push(step);
+        remaining -= step;
+    }
+
+    steps
+}
+
+pub(crate) fn check_arity(arity: usize) {
+    assert!(
+        arity.is_power_of_two() && arity >= 2,
+        "Arity must be a power of two"
+    );
+}
+
+impl<F: ExtensionField, D: Digest> MerkleProof<F, D> {
+    // The height of the tree (log2 of the number of leaves) and its arity are
+    // fixed by the verifier, so the path can't stop at an internal node or go
+    // past the leaves. It ends at the cap node above the leaf.
+    pub fn verify<H>(&self, hasher: &H, cap: &[D], height: usize, arity: usize) -> bool
+    where
+        H: MerkleHasher<F::BaseField, Digest = D>,
+    {
+        if !cap.len().is_power_of_two() {
+            return false;
+        }
+        let cap_height = cap.len().trailing_zeros() as usize;
+        if cap_height > height || self.index >> height != 0 {
+            return false;
+        }
+
+        let steps = path_steps(height, cap_height, arity);
+        if self.siblings.len() != steps.len()
+            || steps
+                .iter()
+                .zip(self.siblings.iter())
+                .any(|(step, group)| group.len() != (1 << step) - 1)
+        {
+            return false;
+        }
+
+        let salt = self.salt.as_ref().map(|salt| salt.as_slice());
+        let mut current_hash = hasher.hash_leaf(&self.leaf, salt);
+        let mut index = self.index;
+        for (step, group) in steps.iter().zip(self.siblings.iter()) {
+            let position = index % (1 << step);
+            let mut children = group.clone();
+            children.insert(position, current_hash);
+            current_hash = hasher.hash_nodes(&children);
+            index >>= step;
         }
 
-        current_hash == self.root
+        current_hash == cap[index]
     }
 }
 
-impl<F: FieldExt<Repr = [u8; 32]>> MerkleTree<F> {
-    pub fn new() -> Self {
-        Self { layers: vec![] }
+impl<F: ExtensionField, H: MerkleHasher<F::BaseField>> Default for MerkleTree<F, H> {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn compute_layers(&mut self, leaves: Vec<F>) -> Vec<F> {
-        self.layers.push(leaves.clone());
+impl<F: ExtensionField, H: MerkleHasher<F::BaseField>> MerkleTree<F, H> {
+    pub fn new() -> Self {
+        Self::with_hasher(H::default())
+    }
 
-        if leaves.len() == 1 {
-            return leaves;
+    pub fn with_hasher(hasher: H) -> Self {
+        Self {
+            hasher,
+            arity: 2,
+            cap_height: 0,
+            leaves: vec![],
+            salts: vec![],
+            layers: vec![],
         }
+    }
 
-        let mut parent_nodes = vec![];
-        for i in (0..leaves.len()).step_by(2) {
-            if leaves.len() - 1 == i {
-                // Sibling.
-                let parent = hash_two(&[leaves[i], leaves[i]]);
-                parent_nodes.push(parent);
-            } else {
-                let left = leaves[i];
-                let right = leaves[i + 1];
-                let parent = hash_two(&[left, right]);
-                parent_nodes.push(parent);
-            }
-        }
-        self.compute_layers(parent_nodes)
+    // Any power of two. Wider nodes make shorter paths, which is cheaper to
+    // verify in a circuit when the hash compresses that many digests at once.
+    pub fn with_arity(mut self, arity: usize) -> Self {
+        check_arity(arity);
+        self.arity = arity;
+        self
+    }
+
+    pub fn with_cap_height(mut self, cap_height: usize) -> Self {
+        self.cap_height = cap_height;
+        self
+    }
+
+    pub fn commit(&mut self, leaves: &[F]) -> H::Digest {
+        self.salts = vec![];
+        self.commit_leaves(leaves)
     }
 
-    pub fn commit(&mut self, leaves: &[F]) -> F {
+    // Commit with a fresh random salt per leaf, so that the root and the paths
+    // don't allow brute-forcing leaves from a small set of values. The salts
+    // are revealed along with the opened leaves.
+    pub fn commit_salted(
+        &mut self,
+        leaves: &[F],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> H::Digest {
+        self.salts = leaves
+            .iter()
+            .map(|_| {
+                let mut salt = [0u8; SALT_LEN];
+                rng.fill_bytes(&mut salt);
+                salt
+            })
+            .collect();
+        self.commit_leaves(leaves)
+    }
+
+    fn commit_leaves(&mut self, leaves: &[F]) -> H::Digest {
         let n = leaves.len();
         assert!(n.is_power_of_two());
+        self.leaves = leaves.to_vec();
+        self.layers = vec![];
 
-        // Add a dummy leaf if the number of leaves is odd.
-        let mut leaves = leaves.to_vec();
-        if n % 2 == 1 {
-            leaves.push(F::zero());
+        // Leaves are hashed, with their salt if any, before grouping.
+        let mut leaves = leaves
+            .iter()
+            .enumerate()
+            .map(|(i, leaf)| {
+                let salt = self.salts.get(i).map(|salt| salt.as_slice());
+                self.hasher.hash_leaf(leaf, salt)
+            })
+            .collect::<Vec<_>>();
+
+        // Pair a single leaf with a dummy leaf.
+        if n == 1 {
+            leaves.push(self.hasher.hash_leaf(&F::zero(), None));
         }
 
-        self.layers.push(leaves.clone());
+        let height = leaves.len().trailing_zeros() as usize;
+        assert!(self.cap_height <= height, "Cap is above the root");
 
-        while leaves.len() != 1 {
-            let mut layer = vec![];
-            for i in (0..leaves.len()).step_by(2) {
-                let left = leaves[i];
-                let right = leaves[i + 1];
-                let parent = hash_two(&[left, right]);
-                layer.push(parent);
-            }
+        // The cap is one of the layers, and the root is above it.
+        let steps = path_steps(height, self.cap_height, self.arity)
+            .into_iter()
+            .chain(path_steps(self.cap_height, 0, self.arity));
+
+        self.layers.push(leaves.clone());
+        for step in steps {
+            let layer = leaves
+                .chunks(1 << step)
+                .map(|children| self.hasher.hash_nodes(children))
+                .collect::<Vec<_>>();
             self.layers.push(layer.clone());
             leaves = layer;
         }
@@ -79,41 +195,41 @@ impl<F: FieldExt<Repr = [u8; 32]>> MerkleTree<F> {
         leaves[0]
     }
 
-    pub fn open(&self, leaf: F) -> MerkleProof<F> {
-        let mut siblings = vec![];
-
-        let leaf_index = self.layers[0].iter().position(|&x| x == leaf).unwrap();
-        let mut sibling_indices = vec![];
-
-        for i in 0..(self.layers.len() - 1) {
-            if i == 0 {
-                sibling_indices.push(if leaf_index % 2 == 0 {
-                    leaf_index + 1
-                } else {
-                    leaf_index - 1
-                });
-            } else {
-                let current_index = sibling_indices[i - 1] / 2;
-                sibling_indices.push(if current_index % 2 == 0 {
-                    if current_index == self.layers[i].len() - 1 {
-                        current_index
-                    } else {
-                        current_index + 1
-                    }
-                } else {
-                    current_index - 1
-                });
-            }
-        }
+    // log2 of the number of leaves
+    pub fn height(&self) -> usize {
+        self.layers[0].len().trailing_zeros() as usize
+    }
 
-        for (i, index) in sibling_indices.iter().enumerate() {
-            siblings.push(self.layers[i][*index]);
-        }
+    fn path_steps(&self) -> Vec<usize> {
+        path_steps(self.height(), self.cap_height, self.arity)
+    }
+
+    pub fn cap(&self) -> &[H::Digest] {
+        &self.layers[self.path_steps().len()]
+    }
+
+    pub fn open(&self, index: usize) -> MerkleProof<F, H::Digest> {
+        let mut position = index;
+        let siblings = self
+            .path_steps()
+            .iter()
+            .zip(self.layers.iter())
+            .map(|(step, layer)| {
+                let start = position >> step << step;
+                let group = (start..(start + (1 << step)))
+                    .filter(|i| *i != position)
+                    .map(|i| layer[i])
+                    .collect();
+                position >>= step;
+                group
+            })
+            .collect();
 
         MerkleProof {
-            root: self.layers.last().unwrap()[0],
-            leaf,
+            leaf: self.leaves[index],
+            index,
             siblings,
+            salt: self.salts.get(index).cloned(),
         }
     }
 }
@@ -121,11 +237,14 @@ impl<F: FieldExt<Repr = [u8; 32]>> MerkleTree<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hasher::PoseidonHasher;
     use pasta_curves::Fp;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
 
     #[test]
     fn test_tree() {
-        let mut tree = MerkleTree::new();
+        let mut tree = MerkleTree::<Fp>::new();
         let leaves = vec![
             Fp::from(1),
             Fp::from(2),
@@ -139,8 +258,206 @@ mod tests {
         tree.commit(&leaves);
 
         for i in 0..leaves.len() {
-            let proof = tree.open(leaves[i]);
-            assert!(proof.verify());
+            let proof = tree.open(i);
+            assert!(proof.verify(&tree.hasher, tree.cap(), tree.height(), 2));
+        }
+
+        // Opening a leaf at the wrong position must fail.
+        let mut proof = tree.open(2);
+        proof.index = 3;
+        assert!(!proof.verify(&tree.hasher, tree.cap(), tree.height(), 2));
+    }
+
+    #[test]
+    fn test_poseidon_tree() {
+        let leaves = (0..16).map(|i| Fp::from(i as u64)).collect::<Vec<_>>();
+        let mut tree = MerkleTree::<Fp, PoseidonHasher<Fp>>::new();
+        let root: Fp = tree.commit(&leaves);
+
+        for i in 0..leaves.len() {
+            let proof = tree.open(i);
+            assert_eq!(tree.cap(), [root]);
+            assert!(proof.verify(&tree.hasher, tree.cap(), tree.height(), 2));
+        }
+    }
+
+    #[test]
+    fn test_salted_tree() {
+        let leaves = (0..8).map(|i| Fp::from(i % 2)).collect::<Vec<_>>();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+
+        let mut tree = MerkleTree::<Fp>::new();
+        let root = tree.commit_salted(&leaves, &mut rng);
+        assert_eq!(tree.salts.len(), leaves.len());
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.open(i);
+            assert_eq!(proof.leaf, *leaf);
+            assert_eq!(proof.salt, Some(tree.salts[i]));
+            assert!(proof.verify(&tree.hasher, tree.cap(), tree.height(), 2));
+        }
+
+        // Equal leaves get different digests, and the root depends on the salts.
+        assert_ne!(tree.layers[0][0], tree.layers[0][2]);
+        let mut other = MerkleTree::<Fp>::new();
+        assert_ne!(other.commit_salted(&leaves, &mut rng), root);
+        assert_ne!(other.commit(&leaves), root);
+        assert!(other.salts.is_empty());
+
+        // The salt is bound to the leaf.
+        let mut proof = tree.open(3);
+        proof.salt.as_mut().unwrap()[0] ^= 1;
+        assert!(!proof.verify(&tree.hasher, tree.cap(), tree.height(), 2));
+
+        let mut proof = tree.open(3);
+        proof.salt = None;
+        assert!(!proof.verify(&tree.hasher, tree.cap(), tree.height(), 2));
+    }
+
+    #[test]
+    fn test_salted_leaf_is_not_a_node() {
+        // A salt and a value whose encodings are those of two nodes are hashed
+        // differently from the parent of those nodes.
+        let hasher = Sha3Hasher;
+        let salt = [3u8; SALT_LEN];
+        let value = Fp::from(4);
+        let right: ByteDigest = value.to_bytes().try_into().unwrap();
+        assert_ne!(
+            hasher.hash_leaf(&value, Some(&salt)),
+            MerkleHasher::<Fp>::hash_nodes(&hasher, &[salt, right])
+        );
+    }
+
+    #[test]
+    fn test_node_is_not_a_leaf() {
+        // With field-element nodes, an internal node is also a valid leaf value.
+        let leaves = (0..8).map(|i| Fp::from(i as u64)).collect::<Vec<_>>();
+        let mut tree = MerkleTree::<Fp, PoseidonHasher<Fp>>::new();
+        tree.commit(&leaves);
+
+        // Node 1 of the layer above the leaves, with the rest of the path of its
+        // children, is a valid path to the root of a shorter tree.
+        let proof = tree.open(2);
+        let forged = MerkleProof {
+            leaf: tree.layers[1][1],
+            index: 1,
+            siblings: proof.siblings[1..].to_vec(),
+            ..proof
+        };
+        assert!(!forged.verify(&tree.hasher, tree.cap(), tree.height(), 2));
+        assert!(!forged.verify(&tree.hasher, tree.cap(), tree.height() - 1, 2));
+
+        // Leaves are hashed before pairing.
+        assert_ne!(tree.layers[0], leaves);
+    }
+
+    #[test]
+    fn test_fixed_height() {
+        let leaves = (0..8).map(|i| Fp::from(i as u64)).collect::<Vec<_>>();
+        let mut tree = MerkleTree::<Fp>::new();
+        tree.commit(&leaves);
+        assert_eq!(tree.height(), 3);
+
+        let proof = tree.open(5);
+        assert!(!proof.verify(&tree.hasher, tree.cap(), 2, 2));
+        assert!(!proof.verify(&tree.hasher, tree.cap(), 4, 2));
+
+        // An index past the leaves that matches on its low bits
+        let mut proof = tree.open(5);
+        proof.index += 8;
+        assert!(!proof.verify(&tree.hasher, tree.cap(), 3, 2));
+    }
+
+    #[test]
+    fn test_cap() {
+        let leaves = (0..16).map(|i| Fp::from(i as u64)).collect::<Vec<_>>();
+        let mut tree = MerkleTree::<Fp>::new().with_cap_height(2);
+        let root = tree.commit(&leaves);
+        assert_eq!(tree.cap().len(), 4);
+        assert_eq!(tree.cap(), tree.layers[2]);
+
+        for i in 0..leaves.len() {
+            let proof = tree.open(i);
+            assert_eq!(proof.siblings.len(), 2);
+            assert!(proof.verify(&tree.hasher, tree.cap(), 4, 2));
+        }
+
+        // The path ends at the cap node above the leaf.
+        let proof = tree.open(5);
+        let mut cap = tree.cap().to_vec();
+        cap[1] = cap[0];
+        assert!(!proof.verify(&tree.hasher, &cap, 4, 2));
+        assert!(!proof.verify(&tree.hasher, &[root], 4, 2));
+        assert!(!proof.verify(&tree.hasher, &tree.cap()[..3], 4, 2));
+
+        // A cap of all the leaf digests leaves nothing to open.
+        let mut tree = MerkleTree::<Fp>::new().with_cap_height(4);
+        tree.commit(&leaves);
+        let proof = tree.open(7);
+        assert!(proof.siblings.is_empty());
+        assert!(proof.verify(&tree.hasher, tree.cap(), 4, 2));
+    }
+
+    #[test]
+    fn test_arity() {
+        let leaves = (0..64).map(|i| Fp::from(i as u64)).collect::<Vec<_>>();
+        for arity in [2, 4, 8, 16] {
+            let mut tree = MerkleTree::<Fp, PoseidonHasher<Fp>>::new().with_arity(arity);
+            let root = tree.commit(&leaves);
+            assert_eq!(tree.cap(), [root]);
+
+            for i in 0..leaves.len() {
+                let proof = tree.open(i);
+                assert!(proof.verify(&tree.hasher, tree.cap(), 6, arity));
+                assert!(proof.siblings.iter().all(|group| group.len() < arity));
+            }
+
+            // The grouping is part of the commitment.
+            let proof = tree.open(9);
+            for other in [2, 4, 8, 16] {
+                assert_eq!(
+                    proof.verify(&tree.hasher, tree.cap(), 6, other),
+                    other == arity
+                );
+            }
+
+            // Siblings are ordered within their group.
+            if arity > 2 {
+                let mut proof = tree.open(9);
+                proof.siblings[0].swap(0, arity - 2);
+                assert!(!proof.verify(&tree.hasher, tree.cap(), 6, arity));
+            }
+        }
+    }
+
+    #[test]
+    fn test_partial_arity() {
+        // With 32 leaves, paths in a 4-ary tree end with a pair.
+        let leaves = (0..32).map(|i| Fp::from(i as u64)).collect::<Vec<_>>();
+        let mut tree = MerkleTree::<Fp>::new().with_arity(4);
+        let root = tree.commit(&leaves);
+        assert_eq!(tree.layers.len(), 4);
+
+        let proof = tree.open(21);
+        let lens = proof.siblings.iter().map(|g| g.len()).collect::<Vec<_>>();
+        assert_eq!(lens, [3, 3, 1]);
+        assert!(proof.verify(&tree.hasher, &[root], 5, 4));
+        assert!(!proof.verify(&tree.hasher, &[root], 4, 4));
+
+        // Paths to a cap end with the pair instead.
+        let mut tree = MerkleTree::<Fp>::new().with_arity(4).with_cap_height(2);
+        tree.commit(&leaves);
+        assert_eq!(tree.cap().len(), 4);
+        for i in 0..leaves.len() {
+            let proof = tree.open(i);
+            assert_eq!(proof.siblings.len(), 2);
+            assert_eq!(proof.siblings[1].len(), 1);
+            assert!(proof.verify(&tree.hasher, tree.cap(), 5, 4));
         }
     }
+
+    #[test]
+    #[should_panic(expected = "Arity must be a power of two")]
+    fn test_invalid_arity() {
+        MerkleTree::<Fp>::new().with_arity(3);
+    }
 }
@@ -1,67 +1,320 @@
 use crate::fft::{fft, ifft};
-use pasta_curves::arithmetic::FieldExt;
-use pasta_curves::group::ff::PrimeField;
-use std::ops::Div;
-use std::process::Output;
+use crate::field::{subgroup, ExtensionField};
+use ff::{Field, PrimeField};
+use std::ops::{Add, Div, Mul, Rem, Sub};
 
+// Products with both factors at least this long are computed with FFTs, if the
+// base field has a large enough two-adic subgroup.
+const FFT_MUL_THRESHOLD: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UniPoly<F>
 where
-    F: FieldExt<Repr = [u8; 32]>,
+    F: ExtensionField,
 {
     pub coeffs: Vec<F>,
 }
 
 impl<F> UniPoly<F>
 where
-    F: FieldExt<Repr = [u8; 32]>,
+    F: ExtensionField,
 {
-    pub fn new(coeffs: Vec<F>) -> Self {
-        Self { coeffs } // [x^0, x^1, x^2, x^3...]
+    // Coefficients are [x^0, x^1, x^2, x^3...], without trailing zeros, so the
+    // zero polynomial has none.
+    pub fn new(mut coeffs: Vec<F>) -> Self {
+        let len = coeffs
+            .iter()
+            .rposition(|c| *c != F::zero())
+            .map_or(0, |i| i + 1);
+        coeffs.truncate(len);
+
+        Self { coeffs }
     }
 
-    pub fn degree(&self) -> usize {
-        self.coeffs.len() - 1
+    pub fn zero() -> Self {
+        Self { coeffs: vec![] }
     }
 
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.iter().all(|c| *c == F::zero())
+    }
+
+    // None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.iter().rposition(|c| *c != F::zero())
+    }
+
+    // Horner's rule
     pub fn eval(&self, x: F) -> F {
-        let mut result = F::zero();
-        for (i, coeff) in self.coeffs.iter().enumerate() {
-            result += *coeff * x.pow(&[i as u64, 0, 0, 0]);
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(F::zero(), |acc, c| acc * x + c)
+    }
+
+    // Evaluate at many arbitrary points with a subproduct tree: the products of
+    // (X - x_i) over ever larger groups of points, down which the polynomial is
+    // reduced until only the constants self(x_i) remain.
+    pub fn eval_many(&self, points: &[F]) -> Vec<F> {
+        if points.len() <= 1 {
+            return points.iter().map(|x| self.eval(*x)).collect();
+        }
+
+        let leaves = points
+            .iter()
+            .map(|x| Self::new(vec![-*x, F::one()]))
+            .collect::<Vec<_>>();
+        let mut tree = vec![leaves];
+        while tree.last().unwrap().len() > 1 {
+            let next_level = tree
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => a * b,
+                    [a] => a.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            tree.push(next_level);
+        }
+
+        let mut remainders = vec![self.clone()];
+        for level in tree.iter().rev() {
+            remainders = level
+                .iter()
+                .enumerate()
+                .map(|(i, m)| &remainders[i / 2] % m)
+                .collect();
+        }
+
+        remainders
+            .iter()
+            .map(|r| r.coeffs.first().cloned().unwrap_or(F::zero()))
+            .collect()
+    }
+
+    // Evaluate over the subgroup of order n with an FFT.
+    pub fn eval_subgroup(&self, n: usize) -> Vec<F> {
+        self.eval_coset(F::BaseField::one(), n)
+    }
+
+    // Evaluate over the coset offset * <w> of the subgroup of order n with an FFT,
+    // as self(offset X) over the subgroup.
+    pub fn eval_coset(&self, offset: F::BaseField, n: usize) -> Vec<F> {
+        assert!(n.is_power_of_two());
+
+        // On the coset X^n = offset^n, so reduce modulo X^n - offset^n first.
+        let offset_n = offset.pow_vartime([n as u64]);
+        let (_, reduced) = self.div_by_vanishing(n, F::from(offset_n));
+
+        let mut coeffs = vec![];
+        let mut offset_pow = F::BaseField::one();
+        for c in &reduced.coeffs {
+            coeffs.push(*c * offset_pow);
+            offset_pow *= offset;
         }
+        coeffs.resize(n, F::zero());
 
-        result
+        fft(&coeffs, &subgroup(n))
     }
 
-    pub fn interpolate(domain: &[F], evals: &[F]) -> Self {
+    pub fn interpolate(domain: &[F::BaseField], evals: &[F]) -> Self {
         assert!(domain.len() == evals.len());
-        let coeffs = ifft(&domain, &evals);
-        let mut degree = 0;
+        Self::new(ifft(domain, evals))
+    }
+
+    pub fn mul_naive(&self, rhs: &Self) -> Self {
+        if self.is_zero() || rhs.is_zero() {
+            return Self::zero();
+        }
+
+        let mut coeffs = vec![F::zero(); self.coeffs.len() + rhs.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            for (j, b) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] += *a * b;
+            }
+        }
+
+        Self::new(coeffs)
+    }
+
+    // Evaluate both over a subgroup of size at least the length of the product,
+    // multiply pointwise and interpolate.
+    pub fn mul_fft(&self, rhs: &Self) -> Self {
+        if self.is_zero() || rhs.is_zero() {
+            return Self::zero();
+        }
+
+        let len = self.coeffs.len() + rhs.coeffs.len() - 1;
+        let domain = subgroup::<F::BaseField>(len.next_power_of_two());
+
+        let mut lhs_coeffs = self.coeffs.clone();
+        let mut rhs_coeffs = rhs.coeffs.clone();
+        lhs_coeffs.resize(domain.len(), F::zero());
+        rhs_coeffs.resize(domain.len(), F::zero());
+
+        let evals = fft(&lhs_coeffs, &domain)
+            .into_iter()
+            .zip(fft(&rhs_coeffs, &domain))
+            .map(|(a, b)| a * b)
+            .collect::<Vec<F>>();
+
+        Self::new(ifft(&domain, &evals))
+    }
+
+    // Long division: self = quotient * divisor + remainder with
+    // deg remainder < deg divisor.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let divisor = Self::new(divisor.coeffs.clone());
+        assert!(!divisor.is_zero(), "Division by the zero polynomial");
+
+        let n = divisor.coeffs.len();
+        let mut remainder = self.coeffs.clone();
+        if remainder.len() < n {
+            return (Self::zero(), Self::new(remainder));
+        }
+
+        let lead_inv = divisor.coeffs[n - 1].invert().unwrap();
+        let mut quotient = vec![F::zero(); remainder.len() - n + 1];
+        for i in (0..quotient.len()).rev() {
+            let q = remainder[i + n - 1] * lead_inv;
+            quotient[i] = q;
+            for (j, d) in divisor.coeffs.iter().enumerate() {
+                remainder[i + j] -= q * d;
+            }
+        }
+        remainder.truncate(n - 1);
+
+        (Self::new(quotient), Self::new(remainder))
+    }
 
-        for i in 0..coeffs.len() {
-            if coeffs[i] != F::zero() {
-                degree = i;
+    // Synthetic division by (X - z): returns the quotient and the remainder self(z).
+    pub fn div_by_linear(&self, z: F) -> (Self, F) {
+        let mut quotient = vec![F::zero(); self.coeffs.len().saturating_sub(1)];
+        let mut acc = F::zero();
+        for (i, c) in self.coeffs.iter().enumerate().rev() {
+            acc = acc * z + c;
+            if i > 0 {
+                quotient[i - 1] = acc;
             }
         }
 
-        Self {
-            coeffs: coeffs[..(degree + 1)].to_vec(),
+        (Self::new(quotient), acc)
+    }
+
+    // Division by X^n - c, e.g. the vanishing polynomial X^n - 1 of a subgroup of
+    // order n or X^n - h^n of the coset h<w>, in linear time since X^n = c mod X^n - c.
+    pub fn div_by_vanishing(&self, n: usize, c: F) -> (Self, Self) {
+        assert!(n > 0);
+        let mut remainder = self.coeffs.clone();
+        if remainder.len() <= n {
+            return (Self::zero(), Self::new(remainder));
+        }
+
+        let mut quotient = vec![F::zero(); remainder.len() - n];
+        for i in (n..remainder.len()).rev() {
+            let q = remainder[i];
+            quotient[i - n] = q;
+            remainder[i - n] += q * c;
+        }
+        remainder.truncate(n);
+
+        (Self::new(quotient), Self::new(remainder))
+    }
+}
+
+impl<'a, F: ExtensionField> Add<&'a UniPoly<F>> for &'a UniPoly<F> {
+    type Output = UniPoly<F>;
+
+    fn add(self, rhs: &'a UniPoly<F>) -> UniPoly<F> {
+        let len = self.coeffs.len().max(rhs.coeffs.len());
+        let coeffs = (0..len)
+            .map(|i| {
+                let a = self.coeffs.get(i).cloned().unwrap_or(F::zero());
+                let b = rhs.coeffs.get(i).cloned().unwrap_or(F::zero());
+                a + b
+            })
+            .collect();
+
+        UniPoly::new(coeffs)
+    }
+}
+
+impl<'a, F: ExtensionField> Sub<&'a UniPoly<F>> for &'a UniPoly<F> {
+    type Output = UniPoly<F>;
+
+    fn sub(self, rhs: &'a UniPoly<F>) -> UniPoly<F> {
+        let len = self.coeffs.len().max(rhs.coeffs.len());
+        let coeffs = (0..len)
+            .map(|i| {
+                let a = self.coeffs.get(i).cloned().unwrap_or(F::zero());
+                let b = rhs.coeffs.get(i).cloned().unwrap_or(F::zero());
+                a - b
+            })
+            .collect();
+
+        UniPoly::new(coeffs)
+    }
+}
+
+impl<'a, F: ExtensionField> Mul<&'a UniPoly<F>> for &'a UniPoly<F> {
+    type Output = UniPoly<F>;
+
+    fn mul(self, rhs: &'a UniPoly<F>) -> UniPoly<F> {
+        if self.is_zero() || rhs.is_zero() {
+            return UniPoly::zero();
+        }
+
+        let len = self.coeffs.len() + rhs.coeffs.len() - 1;
+        let two_adic = len.next_power_of_two() <= 1 << F::BaseField::S;
+        if two_adic && self.coeffs.len().min(rhs.coeffs.len()) >= FFT_MUL_THRESHOLD {
+            self.mul_fft(rhs)
+        } else {
+            self.mul_naive(rhs)
         }
     }
 }
 
-/*
-impl<F: FieldExt<Repr = [> ; 32]>> Div for UniPoly<F {
-    type Output = Self;
+impl<'a, F: ExtensionField> Div<&'a UniPoly<F>> for &'a UniPoly<F> {
+    type Output = UniPoly<F>;
 
-    fn div(self, rhs: Self) -> Self::Output {
-        let evals = fft(domain, self.coeffs.clone());
+    fn div(self, rhs: &'a UniPoly<F>) -> UniPoly<F> {
+        self.div_rem(rhs).0
     }
 }
- */
+
+impl<'a, F: ExtensionField> Rem<&'a UniPoly<F>> for &'a UniPoly<F> {
+    type Output = UniPoly<F>;
+
+    fn rem(self, rhs: &'a UniPoly<F>) -> UniPoly<F> {
+        self.div_rem(rhs).1
+    }
+}
+
+macro_rules! impl_owned_op {
+    ($trait:ident, $method:ident) => {
+        impl<F: ExtensionField> $trait for UniPoly<F> {
+            type Output = UniPoly<F>;
+
+            fn $method(self, rhs: Self) -> UniPoly<F> {
+                (&self).$method(&rhs)
+            }
+        }
+    };
+}
+
+impl_owned_op!(Add, add);
+impl_owned_op!(Sub, sub);
+impl_owned_op!(Mul, mul);
+impl_owned_op!(Div, div);
+impl_owned_op!(Rem, rem);
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::field::subgroup;
+    use ff::Field;
     use pasta_curves::Fp;
 
     #[test]
@@ -74,22 +327,9 @@ mod tests {
             Fp::from(5),
         ];
 
-        let mut domain = vec![];
-        let root_of_unity = Fp::root_of_unity();
-
         let subgroup_order = (coeffs.len() * 2).next_power_of_two();
 
-        // Generator for the subgroup with order _subgroup_order_ in the field
-        let generator = root_of_unity.pow(&[
-            2u32.pow(32 - ((subgroup_order as f64).log2() as u32)) as u64,
-            0,
-            0,
-            0,
-        ]);
-
-        for i in 0..subgroup_order {
-            domain.push(generator.pow(&[i as u64, 0, 0, 0]));
-        }
+        let domain = subgroup::<Fp>(subgroup_order);
 
         let poly = UniPoly {
             coeffs: coeffs.clone(),
@@ -105,4 +345,203 @@ mod tests {
         assert!(interpolant.coeffs == poly.coeffs);
         assert!(interpolant.degree() == poly.degree());
     }
+
+    #[test]
+    fn test_zero() {
+        // All-zero evaluations interpolate to the zero polynomial, not a constant.
+        let domain = subgroup::<Fp>(8);
+        let zero = UniPoly::interpolate(&domain, &[Fp::zero(); 8]);
+        assert_eq!(zero, UniPoly::zero());
+        assert_eq!(zero.degree(), None);
+        assert_eq!(
+            UniPoly::new(vec![Fp::from(3), Fp::zero()]).degree(),
+            Some(0)
+        );
+
+        let a = random_poly(7, 1);
+        assert_eq!(&a * &zero, zero);
+        assert_eq!(a.mul_fft(&zero), zero);
+        assert_eq!(zero.div_rem(&a), (UniPoly::zero(), UniPoly::zero()));
+        assert_eq!(
+            zero.div_by_linear(Fp::from(3)),
+            (UniPoly::zero(), Fp::zero())
+        );
+        assert_eq!(
+            zero.eval_many(&[Fp::one(), Fp::from(2)]),
+            vec![Fp::zero(); 2]
+        );
+        assert_eq!(zero.eval_subgroup(4), vec![Fp::zero(); 4]);
+    }
+
+    // Evaluate as the sum of c_i x^i
+    fn eval_naive(poly: &UniPoly<Fp>, x: Fp) -> Fp {
+        poly.coeffs
+            .iter()
+            .enumerate()
+            .fold(Fp::zero(), |acc, (i, c)| {
+                acc + *c * x.pow_vartime([i as u64])
+            })
+    }
+
+    // Deterministic pseudo-random polynomials
+    fn random_poly(len: usize, seed: u64) -> UniPoly<Fp> {
+        let mut x = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        let coeffs = (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                Fp::from(x)
+            })
+            .collect();
+        UniPoly::new(coeffs)
+    }
+
+    const LENS: [usize; 7] = [1, 2, 3, 7, 16, 65, 100];
+
+    #[test]
+    fn test_add_sub() {
+        for (i, a_len) in LENS.iter().enumerate() {
+            for (j, b_len) in LENS.iter().enumerate() {
+                let a = random_poly(*a_len, i as u64);
+                let b = random_poly(*b_len, 100 + j as u64);
+                let x = Fp::from(1234567);
+
+                assert_eq!((&a + &b).eval(x), a.eval(x) + b.eval(x));
+                assert_eq!((&a - &b).eval(x), a.eval(x) - b.eval(x));
+                assert_eq!(&(&a + &b) - &b, a);
+            }
+        }
+
+        let a = random_poly(10, 1);
+        assert_eq!(&a - &a, UniPoly::zero());
+    }
+
+    #[test]
+    fn test_mul() {
+        for (i, a_len) in LENS.iter().enumerate() {
+            for (j, b_len) in LENS.iter().enumerate() {
+                let a = random_poly(*a_len, i as u64);
+                let b = random_poly(*b_len, 100 + j as u64);
+                let x = Fp::from(7654321);
+
+                let product = a.mul_naive(&b);
+                assert_eq!(
+                    product.degree(),
+                    Some(a.degree().unwrap() + b.degree().unwrap())
+                );
+                assert_eq!(product.eval(x), a.eval(x) * b.eval(x));
+                assert_eq!(a.mul_fft(&b), product);
+                assert_eq!(&a * &b, product);
+            }
+        }
+    }
+
+    #[test]
+    fn test_div_rem() {
+        for (i, a_len) in LENS.iter().enumerate() {
+            for (j, b_len) in LENS.iter().enumerate() {
+                let a = random_poly(*a_len, i as u64);
+                let b = random_poly(*b_len, 100 + j as u64);
+
+                let (q, r) = a.div_rem(&b);
+                assert_eq!(&(&q * &b) + &r, a);
+                assert!(r.is_zero() || r.degree() < b.degree());
+                assert_eq!(&a / &b, q);
+                assert_eq!(&a % &b, r);
+
+                // Exact division
+                let (q, r) = (&a * &b).div_rem(&b);
+                assert_eq!(q, a);
+                assert!(r.is_zero());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by the zero polynomial")]
+    fn test_div_by_zero() {
+        random_poly(5, 1).div_rem(&UniPoly::new(vec![Fp::zero(), Fp::zero()]));
+    }
+
+    #[test]
+    fn test_div_by_linear() {
+        let z = Fp::from(42);
+        let linear = UniPoly::new(vec![-z, Fp::one()]);
+        for (i, len) in LENS.iter().enumerate() {
+            let a = random_poly(*len, i as u64);
+
+            let (q, r) = a.div_by_linear(z);
+            assert_eq!(r, a.eval(z));
+            assert_eq!(&(&q * &linear) + &UniPoly::new(vec![r]), a);
+            assert_eq!(a.div_rem(&linear), (q, UniPoly::new(vec![r])));
+        }
+    }
+
+    #[test]
+    fn test_div_by_vanishing() {
+        for n in [1, 4, 16] {
+            // X^n - 1 and X^n - h^n
+            for c in [Fp::one(), Fp::from(5).pow_vartime([n as u64])] {
+                let mut vanishing = vec![Fp::zero(); n + 1];
+                vanishing[0] = -c;
+                vanishing[n] = Fp::one();
+                let vanishing = UniPoly::new(vanishing);
+
+                for (i, len) in LENS.iter().enumerate() {
+                    let a = random_poly(*len, i as u64);
+                    assert_eq!(a.div_by_vanishing(n, c), a.div_rem(&vanishing));
+                }
+            }
+        }
+
+        // The remainder by X^8 - 1 agrees with the polynomial on the subgroup of order 8.
+        let a = random_poly(20, 3);
+        let (_, r) = a.div_by_vanishing(8, Fp::one());
+        for x in subgroup::<Fp>(8) {
+            assert_eq!(r.eval(x), a.eval(x));
+        }
+    }
+
+    #[test]
+    fn test_eval() {
+        for (i, len) in LENS.iter().enumerate() {
+            let a = random_poly(*len, i as u64);
+            let points = random_poly(*len + 3, 200 + i as u64).coeffs;
+
+            let expected = points
+                .iter()
+                .map(|x| eval_naive(&a, *x))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                points.iter().map(|x| a.eval(*x)).collect::<Vec<_>>(),
+                expected
+            );
+            assert_eq!(a.eval_many(&points), expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_coset() {
+        let offset = Fp::from(5);
+        for (i, len) in LENS.iter().enumerate() {
+            let a = random_poly(*len, i as u64);
+
+            // Domains both larger and smaller than the polynomial
+            for n in [1, 8, 128] {
+                let domain = subgroup::<Fp>(n);
+                let expected = domain
+                    .iter()
+                    .map(|x| eval_naive(&a, *x))
+                    .collect::<Vec<_>>();
+                assert_eq!(a.eval_subgroup(n), expected);
+
+                let expected = domain
+                    .iter()
+                    .map(|x| eval_naive(&a, offset * x))
+                    .collect::<Vec<_>>();
+                assert_eq!(a.eval_coset(offset, n), expected);
+            }
+        }
+    }
 }
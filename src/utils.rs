@@ -1,75 +1,94 @@
-use merlin::Transcript;
-use pasta_curves::arithmetic::FieldExt;
+use crate::field::FriField;
+use crate::transcript::FiatShamirTranscript;
 
-pub fn hash_two<F>(values: &[F; 2]) -> F
-where
-    F: FieldExt<Repr = [u8; 32]>,
-{
-    let mut bytes = vec![];
-    bytes.extend_from_slice(&values[0].to_repr());
-    bytes.resize(32, 0);
-    bytes.extend_from_slice(&values[1].to_repr());
-    bytes.resize(32, 0);
-
-    let mut bytes_8 = vec![];
-    for i in 0..(bytes.len() / 8) {
-        let mut acc: u64 = 0;
-        for j in 0..8 {
-            acc += (bytes[8 * i + j] as u64) << j;
-        }
-        bytes_8.push(acc);
-    }
-
-    bytes_8.resize(25, 0);
-
-    keccak::f1600(&mut bytes_8.as_slice().try_into().unwrap());
-
-    let mut bytes = bytes_8[0..16]
-        .iter()
-        .flat_map(|x| x.to_le_bytes().to_vec())
-        .collect::<Vec<u8>>();
-    bytes.reverse();
-
-    F::from_bytes_wide(&bytes[0..64].try_into().unwrap())
-}
-
-fn sample_index(random_bytes: [u8; 64], size: usize) -> usize {
-    let mut acc: u64 = 0;
-    for b in random_bytes {
-        acc = acc << 8 ^ (b as u64);
-    }
-
-    (acc % (size as u64)) as usize
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SamplingError {
+    // More distinct indices were requested than there are reduced indices.
+    NotEnoughIndices { requested: usize, available: usize },
 }
 
-pub fn sample_indices(
+// Sample num_indices indices below max_index. Unless duplicates are allowed,
+// their reductions modulo reduced_max_index (the query positions in the last
+// committed layer) are all distinct.
+pub fn sample_indices<F, T>(
     num_indices: usize,
     max_index: usize,
     reduced_max_index: usize,
-    transcript: &mut Transcript,
-) -> Vec<usize> {
-    assert!(num_indices <= 2 * reduced_max_index, "not enough entropy!");
-    assert!(num_indices <= reduced_max_index);
+    allow_duplicates: bool,
+    transcript: &mut T,
+) -> Result<Vec<usize>, SamplingError>
+where
+    F: FriField,
+    T: FiatShamirTranscript<F>,
+{
+    if !allow_duplicates && num_indices > reduced_max_index {
+        return Err(SamplingError::NotEnoughIndices {
+            requested: num_indices,
+            available: reduced_max_index,
+        });
+    }
 
     let mut indices = vec![];
-
     let mut reduced_indices = vec![];
 
-    let mut counter = 0;
-
     while indices.len() < num_indices {
-        let mut random_bytes = [0u8; 64];
-        transcript.append_u64(b"counter", counter);
-        transcript.challenge_bytes(b"index", &mut random_bytes);
-        let index = sample_index(random_bytes, max_index);
+        let index = transcript.squeeze_index(b"index", max_index);
         let reduced_index = index % reduced_max_index;
 
-        counter += 1;
-        if !reduced_indices.contains(&reduced_index) {
+        if allow_duplicates || !reduced_indices.contains(&reduced_index) {
             reduced_indices.push(reduced_index);
             indices.push(index);
-        };
+        }
     }
 
-    indices
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use merlin::Transcript;
+    use pasta_curves::Fp;
+
+    #[test]
+    fn test_sample_indices() {
+        for max_index in [16, 24, 1000] {
+            let mut transcript = Transcript::new(b"test_sampling");
+            let indices = sample_indices::<Fp, _>(8, max_index, 8, false, &mut transcript).unwrap();
+            assert!(indices.iter().all(|i| *i < max_index));
+
+            let mut reduced = indices.iter().map(|i| i % 8).collect::<Vec<_>>();
+            reduced.sort();
+            assert_eq!(reduced, (0..8).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_not_enough_indices() {
+        let mut transcript = Transcript::new(b"test_sampling");
+        assert_eq!(
+            sample_indices::<Fp, _>(9, 16, 8, false, &mut transcript),
+            Err(SamplingError::NotEnoughIndices {
+                requested: 9,
+                available: 8
+            })
+        );
+
+        // With duplicates allowed, any number of queries can be drawn.
+        let indices = sample_indices::<Fp, _>(20, 16, 8, true, &mut transcript).unwrap();
+        assert_eq!(indices.len(), 20);
+        assert!(indices.iter().all(|i| *i < 16));
+    }
+
+    #[test]
+    fn test_uniform() {
+        // Rough check over a non-power-of-two size: every index shows up about
+        // equally often.
+        let mut transcript = Transcript::new(b"test_sampling");
+        let mut counts = [0usize; 6];
+        for i in sample_indices::<Fp, _>(6000, 6, 6, true, &mut transcript).unwrap() {
+            counts[i] += 1;
+        }
+        assert!(counts.iter().all(|c| (800..1200).contains(c)));
+    }
 }